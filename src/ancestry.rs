@@ -0,0 +1,245 @@
+//! Static index of the ancestors of every node.
+//!
+//! [`AncestryIndex`] numbers the nodes of a tree in pre-order, so that the
+//! descendants of a node are numbered right after it, and keeps the ancestors
+//! of every node at power-of-two distances. This answers ancestry queries in
+//! constant or logarithmic time, for as long as the structure of the tree
+//! does not change. Trees keep a version of their structure, so that an index
+//! can tell whether it is still current.
+
+use std::iter;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::iter::Edge;
+use crate::{Error, NodeId, Tree};
+
+/// Source of the sequences of versions of trees.
+static SEQUENCES: AtomicU64 = AtomicU64::new(0);
+
+/// Version of the structure of a tree, bumped by every change to it.
+///
+/// Clones start a new sequence, since they change independently of the
+/// original.
+#[derive(Debug)]
+pub(crate) struct Version {
+    sequence: u64,
+    count: u64,
+}
+
+impl Version {
+    pub(crate) fn bump(&mut self) {
+        self.count = self.count.wrapping_add(1);
+    }
+
+    fn get(&self) -> (u64, u64) {
+        (self.sequence, self.count)
+    }
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version {
+            sequence: SEQUENCES.fetch_add(1, Ordering::Relaxed),
+            count: 0,
+        }
+    }
+}
+
+impl Clone for Version {
+    fn clone(&self) -> Self {
+        Version::default()
+    }
+}
+
+/// Pre-order number of vacant slots.
+const VACANT: u32 = u32::MAX;
+
+/// Index of the ancestors of every node of a tree.
+///
+/// Built in `O(n log n)` time from a tree, after which
+/// [`is_ancestor`](AncestryIndex::is_ancestor) runs in constant time and
+/// [`lca`](AncestryIndex::lca) and
+/// [`kth_ancestor`](AncestryIndex::kth_ancestor) in logarithmic time.
+///
+/// The index does not borrow the tree. Its answers describe the tree as it
+/// was when the index was built; [`is_current`](AncestryIndex::is_current)
+/// tells whether the structure of the tree has changed since.
+///
+/// # Examples
+///
+/// ```
+/// use ego_tree::{tree, AncestryIndex};
+///
+/// let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+/// let b = tree.root().first_child().unwrap().id();
+/// let c = tree.get(b).unwrap().first_child().unwrap().id();
+/// let d = tree.root().last_child().unwrap().id();
+///
+/// let index = AncestryIndex::new(&tree);
+/// assert!(index.is_ancestor(b, c));
+/// assert_eq!(Some(tree.root().id()), index.lca(c, d));
+/// assert_eq!(Some(b), index.kth_ancestor(c, 1));
+///
+/// tree.get_mut(d).unwrap().append_id(c);
+/// assert!(!index.is_current(&tree));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AncestryIndex {
+    /// Version of the tree the index was built from.
+    version: (u64, u64),
+
+    /// Pre-order number of the node in every slot.
+    numbers: Vec<u32>,
+
+    /// IDs of the nodes, by number.
+    ids: Vec<NodeId>,
+
+    /// Number of the last descendant of every node, by number.
+    last: Vec<u32>,
+
+    /// Depth of every node, by number.
+    depth: Vec<u32>,
+
+    /// Numbers of the ancestors at distance 2^i of every node, by number,
+    /// or the number of the topmost ancestor if there is none.
+    up: Vec<Vec<u32>>,
+}
+
+impl AncestryIndex {
+    /// Builds the index of a tree.
+    ///
+    /// Orphans and their descendants are indexed as well, numbered after
+    /// the nodes under the root.
+    pub fn new<T>(tree: &Tree<T>) -> Self {
+        let len = tree.vec.len() - tree.vacant;
+        let mut numbers = vec![VACANT; tree.vec.len()];
+        let mut ids = Vec::with_capacity(len);
+        let mut last = vec![0; len];
+        let mut depth = Vec::with_capacity(len);
+        let mut parents = Vec::with_capacity(len);
+
+        let mut open = Vec::new();
+        let tops = iter::once(tree.root()).chain(tree.nodes().filter(|node| node.is_orphan()));
+        for edge in tops.flat_map(|top| top.traverse()) {
+            match edge {
+                Edge::Open(node) => {
+                    let number = ids.len() as u32;
+                    numbers[node.id.to_index()] = number;
+                    ids.push(node.id);
+                    depth.push(open.len() as u32);
+                    parents.push(open.last().copied().unwrap_or(number));
+                    open.push(number);
+                }
+                Edge::Close(_) => {
+                    let number = open.pop().unwrap();
+                    last[number as usize] = ids.len() as u32 - 1;
+                }
+            }
+        }
+
+        let max_depth = depth.iter().copied().max().unwrap_or(0);
+        let mut up = vec![parents];
+        while 1 << up.len() <= max_depth {
+            let prev = up.last().unwrap();
+            let next = prev.iter().map(|&number| prev[number as usize]).collect();
+            up.push(next);
+        }
+
+        AncestryIndex {
+            version: tree.version.get(),
+            numbers,
+            ids,
+            last,
+            depth,
+            up,
+        }
+    }
+
+    /// Returns true if the structure of the tree has not changed since the
+    /// index was built from it.
+    ///
+    /// Changing node values does not count as a change. Returns false for a
+    /// tree the index was not built from, including clones of it.
+    pub fn is_current<T>(&self, tree: &Tree<T>) -> bool {
+        self.version == tree.version.get()
+    }
+
+    fn number(&self, id: NodeId) -> u32 {
+        match self.numbers.get(id.to_index()) {
+            Some(&number) if number != VACANT && self.ids[number as usize] == id => number,
+            _ => panic!("{}", Error::InvalidId(id)),
+        }
+    }
+
+    /// Returns true if the node numbered `ancestor` is the node numbered
+    /// `number` or one of its ancestors.
+    fn contains(&self, ancestor: u32, number: u32) -> bool {
+        ancestor <= number && number <= self.last[ancestor as usize]
+    }
+
+    /// Returns the number of ancestors of a node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not valid in the indexed tree.
+    pub fn depth(&self, id: NodeId) -> usize {
+        self.depth[self.number(id) as usize] as usize
+    }
+
+    /// Returns true if `ancestor` is an ancestor of `id`, in constant time.
+    ///
+    /// A node is not its own ancestor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either ID is not valid in the indexed tree.
+    pub fn is_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+        let (ancestor, number) = (self.number(ancestor), self.number(id));
+        ancestor != number && self.contains(ancestor, number)
+    }
+
+    /// Returns the lowest common ancestor of two nodes, in logarithmic time.
+    ///
+    /// If one node is an ancestor of the other, that node is returned.
+    /// Returns `None` if the nodes are in different components, such as
+    /// the root and an orphan.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either ID is not valid in the indexed tree.
+    pub fn lca(&self, a: NodeId, b: NodeId) -> Option<NodeId> {
+        let (mut a, b) = (self.number(a), self.number(b));
+        if self.contains(a, b) {
+            return Some(self.ids[a as usize]);
+        }
+        for up in self.up.iter().rev() {
+            let ancestor = up[a as usize];
+            if !self.contains(ancestor, b) {
+                a = ancestor;
+            }
+        }
+        let parent = self.up[0][a as usize];
+        self.contains(parent, b).then(|| self.ids[parent as usize])
+    }
+
+    /// Returns the ancestor `k` levels above a node, in logarithmic time.
+    ///
+    /// Returns the node itself if `k` is zero, and `None` if `k` is greater
+    /// than the depth of the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not valid in the indexed tree.
+    pub fn kth_ancestor(&self, id: NodeId, k: usize) -> Option<NodeId> {
+        let mut number = self.number(id);
+        if k > self.depth[number as usize] as usize {
+            return None;
+        }
+        for (i, up) in self.up.iter().enumerate() {
+            if k >> i & 1 == 1 {
+                number = up[number as usize];
+            }
+        }
+        Some(self.ids[number as usize])
+    }
+}
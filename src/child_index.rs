@@ -0,0 +1,249 @@
+//! Opt-in index of the children of every node.
+//!
+//! Sibling links form a linked list, so finding the n-th child or the
+//! position of a node walks the list. When enabled, the tree also keeps the
+//! children of every node in a `Vec`, along with the position of every node
+//! among its siblings, and updates them on every change to the structure.
+
+use crate::{NodeId, NodeMut, NodeRef, Slot, Tree};
+
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    children: Vec<NodeId>,
+    position: usize,
+}
+
+/// Children of every node, indexed by slot. Empty when disabled.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChildIndex {
+    entries: Option<Vec<Entry>>,
+}
+
+impl ChildIndex {
+    fn entry(&mut self, id: NodeId) -> Option<&mut Entry> {
+        let entries = self.entries.as_mut()?;
+        let index = id.to_index();
+        if index >= entries.len() {
+            entries.resize_with(index + 1, Entry::default);
+        }
+        Some(&mut entries[index])
+    }
+
+    /// Returns the children of a node, if the index is enabled.
+    pub(crate) fn children(&self, id: NodeId) -> Option<&[NodeId]> {
+        let entries = self.entries.as_ref()?;
+        Some(
+            entries
+                .get(id.to_index())
+                .map_or(&[], |entry| &entry.children),
+        )
+    }
+
+    /// Returns the position of a node among its siblings, if the index is enabled.
+    pub(crate) fn position(&self, id: NodeId) -> Option<usize> {
+        let entries = self.entries.as_ref()?;
+        Some(entries.get(id.to_index()).map_or(0, |entry| entry.position))
+    }
+
+    /// Builds the index of every node.
+    pub(crate) fn enable<T>(&mut self, slots: &[Slot<T>]) {
+        self.entries = Some(Vec::with_capacity(slots.len()));
+        self.rebuild_range(slots, 0);
+    }
+
+    pub(crate) fn disable(&mut self) {
+        self.entries = None;
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.entries.is_some()
+    }
+
+    /// Rebuilds the entries of the nodes in the slots from `start` on,
+    /// which must not be children of nodes before `start`.
+    pub(crate) fn rebuild_range<T>(&mut self, slots: &[Slot<T>], start: usize) {
+        let Some(entries) = &mut self.entries else {
+            return;
+        };
+        entries.truncate(start);
+        entries.resize_with(slots.len(), Entry::default);
+        for index in start..slots.len() {
+            if let Some(node) = slots[index].node() {
+                if node.children.is_some() {
+                    let id = unsafe { NodeId::from_index(index, slots[index].generation()) };
+                    self.rebuild(slots, id);
+                }
+            }
+        }
+    }
+
+    /// Rebuilds the children of a node from the sibling links.
+    pub(crate) fn rebuild<T>(&mut self, slots: &[Slot<T>], parent: NodeId) {
+        let Some(entry) = self.entry(parent) else {
+            return;
+        };
+        let node = |id: NodeId| slots[id.to_index()].node().unwrap();
+        let mut children = std::mem::take(&mut entry.children);
+        children.clear();
+        let mut child = node(parent).children.map(|(id, _)| id);
+        while let Some(id) = child {
+            children.push(id);
+            child = node(id).next_sibling;
+        }
+        self.set_children(parent, children, 0);
+    }
+
+    /// Stores the children of a node, updating the positions from `start` on.
+    fn set_children(&mut self, parent: NodeId, children: Vec<NodeId>, start: usize) {
+        for (position, &id) in children.iter().enumerate().skip(start) {
+            self.entry(id).unwrap().position = position;
+        }
+        self.entry(parent).unwrap().children = children;
+    }
+
+    /// Clears the children and position of a node without parent or children.
+    pub(crate) fn reset(&mut self, id: NodeId) {
+        if let Some(entry) = self.entry(id) {
+            entry.children.clear();
+            entry.position = 0;
+        }
+    }
+
+    /// Clears the position of a node without parent.
+    pub(crate) fn reset_position(&mut self, id: NodeId) {
+        if let Some(entry) = self.entry(id) {
+            entry.position = 0;
+        }
+    }
+
+    /// Inserts a child at a position.
+    pub(crate) fn insert(&mut self, parent: NodeId, position: usize, id: NodeId) {
+        let Some(entry) = self.entry(parent) else {
+            return;
+        };
+        let mut children = std::mem::take(&mut entry.children);
+        children.insert(position, id);
+        self.set_children(parent, children, position);
+    }
+
+    /// Appends a child.
+    pub(crate) fn push(&mut self, parent: NodeId, id: NodeId) {
+        let Some(entry) = self.entry(parent) else {
+            return;
+        };
+        let position = entry.children.len();
+        entry.children.push(id);
+        self.entry(id).unwrap().position = position;
+    }
+
+    /// Removes a child.
+    pub(crate) fn remove(&mut self, parent: NodeId, id: NodeId) {
+        let Some(position) = self.position(id) else {
+            return;
+        };
+        let mut children = std::mem::take(&mut self.entry(parent).unwrap().children);
+        children.remove(position);
+        self.set_children(parent, children, position);
+        self.entry(id).unwrap().position = 0;
+    }
+
+    /// Drops the entries of slots from `len` on.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if let Some(entries) = &mut self.entries {
+            entries.truncate(len);
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    /// Enables the child index, making [`NodeRef::nth_child`] and
+    /// [`NodeRef::sibling_index`] constant time.
+    ///
+    /// The index is built in linear time and kept up to date by every change
+    /// to the tree. Appending a child or removing the last one stays constant
+    /// time, while other changes cost time linear in the number of siblings
+    /// of the nodes involved, like inserting into a `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    /// tree.enable_child_index();
+    /// assert_eq!(3, tree.root().child_count());
+    /// assert_eq!(&'c', tree.root().nth_child(1).unwrap().value());
+    /// ```
+    pub fn enable_child_index(&mut self) {
+        if !self.child_index.is_enabled() {
+            self.child_index.enable(&self.vec);
+        }
+    }
+
+    /// Disables the child index and releases its memory.
+    pub fn disable_child_index(&mut self) {
+        self.child_index.disable();
+    }
+
+    /// Returns true if the child index is enabled.
+    pub fn has_child_index(&self) -> bool {
+        self.child_index.is_enabled()
+    }
+}
+
+impl<'a, T: 'a> NodeRef<'a, T> {
+    /// Returns the number of children.
+    ///
+    /// Runs in constant time, since every node keeps count of its children.
+    pub fn child_count(&self) -> usize {
+        self.node.child_count as usize
+    }
+
+    /// Returns the child at position `n`, counting from zero.
+    ///
+    /// Runs in constant time if the tree has a child index, otherwise in time
+    /// linear in `n`.
+    pub fn nth_child(&self, n: usize) -> Option<Self> {
+        match self.tree.child_index.children(self.id) {
+            Some(children) => children
+                .get(n)
+                .map(|&id| unsafe { self.tree.get_unchecked(id) }),
+            None => self.children().nth(n),
+        }
+    }
+
+    /// Returns the position of this node among its siblings, counting from zero.
+    ///
+    /// Returns zero for the root and orphans. Runs in constant time if the
+    /// tree has a child index, otherwise in time linear in the position.
+    pub fn sibling_index(&self) -> usize {
+        match self.tree.child_index.position(self.id) {
+            Some(position) => position,
+            None => self.prev_siblings().count(),
+        }
+    }
+}
+
+impl<'a, T: 'a> NodeMut<'a, T> {
+    /// Returns the number of children.
+    ///
+    /// See [`NodeRef::child_count`].
+    pub fn child_count(&self) -> usize {
+        unsafe { self.tree.get_unchecked(self.id).child_count() }
+    }
+
+    /// Returns the child at position `n`, counting from zero.
+    ///
+    /// See [`NodeRef::nth_child`].
+    pub fn nth_child(&mut self, n: usize) -> Option<NodeMut<'_, T>> {
+        let id = unsafe { self.tree.get_unchecked(self.id).nth_child(n) }?.id;
+        Some(unsafe { self.tree.get_unchecked_mut(id) })
+    }
+
+    /// Returns the position of this node among its siblings, counting from zero.
+    ///
+    /// See [`NodeRef::sibling_index`].
+    pub fn sibling_index(&self) -> usize {
+        unsafe { self.tree.get_unchecked(self.id).sibling_index() }
+    }
+}
@@ -2,34 +2,47 @@ use std::iter::FusedIterator;
 use std::ops::Range;
 use std::{slice, vec};
 
-use crate::{Node, NodeId, NodeRef, Tree};
+use crate::{NodeRef, Slot, Tree};
 
-/// Iterator that moves out of a tree in insert order.
+/// Iterator that moves out of a tree in slot order.
 #[derive(Debug)]
-pub struct IntoIter<T>(vec::IntoIter<Node<T>>);
+pub struct IntoIter<T> {
+    iter: vec::IntoIter<Slot<T>>,
+    len: usize,
+}
 impl<T> ExactSizeIterator for IntoIter<T> {}
 impl<T> FusedIterator for IntoIter<T> {}
 impl<T> Iterator for IntoIter<T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|node| node.value)
+        let node = self.iter.by_ref().find_map(Slot::into_node)?;
+        self.len -= 1;
+        Some(node.value)
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        (self.len, Some(self.len))
     }
 }
 impl<T> DoubleEndedIterator for IntoIter<T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.0.next_back().map(|node| node.value)
+        let node = self.iter.by_ref().rev().find_map(Slot::into_node)?;
+        self.len -= 1;
+        Some(node.value)
     }
 }
 
-/// Iterator over values in insert order.
+/// Iterator over values in slot order.
 #[derive(Debug)]
-pub struct Values<'a, T: 'a>(slice::Iter<'a, Node<T>>);
+pub struct Values<'a, T: 'a> {
+    iter: slice::Iter<'a, Slot<T>>,
+    len: usize,
+}
 impl<'a, T: 'a> Clone for Values<'a, T> {
     fn clone(&self) -> Self {
-        Values(self.0.clone())
+        Values {
+            iter: self.iter.clone(),
+            len: self.len,
+        }
     }
 }
 impl<'a, T: 'a> ExactSizeIterator for Values<'a, T> {}
@@ -37,49 +50,62 @@ impl<'a, T: 'a> FusedIterator for Values<'a, T> {}
 impl<'a, T: 'a> Iterator for Values<'a, T> {
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|node| &node.value)
+        let node = self.iter.by_ref().find_map(Slot::node)?;
+        self.len -= 1;
+        Some(&node.value)
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        (self.len, Some(self.len))
     }
 }
 impl<'a, T: 'a> DoubleEndedIterator for Values<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.0.next_back().map(|node| &node.value)
+        let node = self.iter.by_ref().rev().find_map(Slot::node)?;
+        self.len -= 1;
+        Some(&node.value)
     }
 }
 
-/// Mutable iterator over values in insert order.
+/// Mutable iterator over values in slot order.
 #[derive(Debug)]
-pub struct ValuesMut<'a, T: 'a>(slice::IterMut<'a, Node<T>>);
+pub struct ValuesMut<'a, T: 'a> {
+    iter: slice::IterMut<'a, Slot<T>>,
+    len: usize,
+}
 impl<'a, T: 'a> ExactSizeIterator for ValuesMut<'a, T> {}
 impl<'a, T: 'a> FusedIterator for ValuesMut<'a, T> {}
 impl<'a, T: 'a> Iterator for ValuesMut<'a, T> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|node| &mut node.value)
+        let node = self.iter.by_ref().find_map(Slot::node_mut)?;
+        self.len -= 1;
+        Some(&mut node.value)
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        (self.len, Some(self.len))
     }
 }
 impl<'a, T: 'a> DoubleEndedIterator for ValuesMut<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.0.next_back().map(|node| &mut node.value)
+        let node = self.iter.by_ref().rev().find_map(Slot::node_mut)?;
+        self.len -= 1;
+        Some(&mut node.value)
     }
 }
 
-/// Iterator over nodes in insert order.
+/// Iterator over nodes in slot order.
 #[derive(Debug)]
 pub struct Nodes<'a, T: 'a> {
     tree: &'a Tree<T>,
     iter: Range<usize>,
+    len: usize,
 }
 impl<'a, T: 'a> Clone for Nodes<'a, T> {
     fn clone(&self) -> Self {
         Self {
             tree: self.tree,
             iter: self.iter.clone(),
+            len: self.len,
         }
     }
 }
@@ -88,19 +114,25 @@ impl<'a, T: 'a> FusedIterator for Nodes<'a, T> {}
 impl<'a, T: 'a> Iterator for Nodes<'a, T> {
     type Item = NodeRef<'a, T>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter
-            .next()
-            .map(|i| unsafe { self.tree.get_unchecked(NodeId::from_index(i)) })
+        let tree = self.tree;
+        let node = self.iter.by_ref().find_map(|i| tree.get(tree.id_at(i)))?;
+        self.len -= 1;
+        Some(node)
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iter.size_hint()
+        (self.len, Some(self.len))
     }
 }
 impl<'a, T: 'a> DoubleEndedIterator for Nodes<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter
-            .next_back()
-            .map(|i| unsafe { self.tree.get_unchecked(NodeId::from_index(i)) })
+        let tree = self.tree;
+        let node = self
+            .iter
+            .by_ref()
+            .rev()
+            .find_map(|i| tree.get(tree.id_at(i)))?;
+        self.len -= 1;
+        Some(node)
     }
 }
 
@@ -108,26 +140,43 @@ impl<T> IntoIterator for Tree<T> {
     type Item = T;
     type IntoIter = IntoIter<T>;
     fn into_iter(self) -> Self::IntoIter {
-        IntoIter(self.vec.into_iter())
+        IntoIter {
+            len: self.len(),
+            iter: self.vec.into_iter(),
+        }
     }
 }
 
 impl<T> Tree<T> {
-    /// Returns an iterator over values in insert order.
-    pub fn values(&self) -> Values<T> {
-        Values(self.vec.iter())
+    /// Returns an iterator over values in slot order.
+    ///
+    /// Nodes are stored in insert order, except that new nodes reuse the
+    /// slots of removed nodes, so they can come before older nodes.
+    pub fn values(&self) -> Values<'_, T> {
+        Values {
+            iter: self.vec.iter(),
+            len: self.len(),
+        }
     }
 
-    /// Returns a mutable iterator over values in insert order.
-    pub fn values_mut(&mut self) -> ValuesMut<T> {
-        ValuesMut(self.vec.iter_mut())
+    /// Returns a mutable iterator over values in slot order.
+    ///
+    /// See [`Tree::values`].
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        ValuesMut {
+            len: self.len(),
+            iter: self.vec.iter_mut(),
+        }
     }
 
-    /// Returns an iterator over nodes in insert order.
-    pub fn nodes(&self) -> Nodes<T> {
+    /// Returns an iterator over nodes in slot order.
+    ///
+    /// See [`Tree::values`].
+    pub fn nodes(&self) -> Nodes<'_, T> {
         Nodes {
             tree: self,
             iter: 0..self.vec.len(),
+            len: self.len(),
         }
     }
 }
@@ -178,20 +227,23 @@ axis_iterators! {
 pub struct Children<'a, T: 'a> {
     front: Option<NodeRef<'a, T>>,
     back: Option<NodeRef<'a, T>>,
+    len: usize,
 }
 impl<'a, T: 'a> Clone for Children<'a, T> {
     fn clone(&self) -> Self {
         Self {
             front: self.front,
             back: self.back,
+            len: self.len,
         }
     }
 }
+impl<'a, T: 'a> ExactSizeIterator for Children<'a, T> {}
 impl<'a, T: 'a> FusedIterator for Children<'a, T> {}
 impl<'a, T: 'a> Iterator for Children<'a, T> {
     type Item = NodeRef<'a, T>;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.front == self.back {
+        let node = if self.front == self.back {
             let node = self.front.take();
             self.back = None;
             node
@@ -199,12 +251,17 @@ impl<'a, T: 'a> Iterator for Children<'a, T> {
             let node = self.front.take();
             self.front = node.as_ref().and_then(NodeRef::next_sibling);
             node
-        }
+        };
+        self.len -= node.is_some() as usize;
+        node
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
     }
 }
 impl<'a, T: 'a> DoubleEndedIterator for Children<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        if self.back == self.front {
+        let node = if self.back == self.front {
             let node = self.back.take();
             self.front = None;
             node
@@ -212,7 +269,9 @@ impl<'a, T: 'a> DoubleEndedIterator for Children<'a, T> {
             let node = self.back.take();
             self.back = node.as_ref().and_then(NodeRef::prev_sibling);
             node
-        }
+        };
+        self.len -= node.is_some() as usize;
+        node
     }
 }
 
@@ -338,6 +397,7 @@ impl<'a, T: 'a> NodeRef<'a, T> {
         Children {
             front: self.first_child(),
             back: self.last_child(),
+            len: self.child_count(),
         }
     }
 
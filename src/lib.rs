@@ -5,10 +5,14 @@
 //! - Trees have at least a root node;
 //! - Nodes have zero or more ordered children;
 //! - Nodes have at most one parent;
-//! - Nodes can be detached (orphaned) but not removed;
-//! - Node parent, next sibling, previous sibling, first child and last child
-//!   can be accessed in constant time;
-//! - All methods perform in constant time;
+//! - Nodes can be detached (orphaned) or removed along with their descendants;
+//! - Slots of removed nodes are reused by nodes created later;
+//! - Node parent, next sibling, previous sibling, first child, last child and
+//!   number of children can be accessed in constant time;
+//! - Creating, appending, detaching and inserting nodes perform in constant
+//!   time, apart from the cycle check when moving a node with children;
+//! - Methods that walk or rebuild the tree, such as [`Tree::compact`],
+//!   [`NodeRef::depth`] or [`NodeRef::height`], document their cost;
 //! - All iterators perform in linear time.
 //!
 //! # Examples
@@ -36,7 +40,8 @@
 )]
 
 use std::fmt::{self, Debug, Display, Formatter};
-use std::num::NonZeroUsize;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU32;
 
 #[cfg(feature = "serde")]
 pub mod serde;
@@ -44,27 +49,281 @@ pub mod serde;
 /// Vec-backed ID-tree.
 ///
 /// Always contains at least a root node.
-#[derive(Clone, PartialEq, Eq, Hash)]
+///
+/// Trees are equal if they have the same root and the same nodes in the same
+/// slots, so that every ID refers to the same node in both. Vacant slots are
+/// not compared.
+#[derive(Clone)]
 pub struct Tree<T> {
-    vec: Vec<Node<T>>,
+    vec: Vec<Slot<T>>,
+
+    /// ID of the root node.
+    root: NodeId,
+
+    /// Head of the list of vacant slots.
+    free: Option<NodeId>,
+
+    /// Number of vacant slots.
+    vacant: usize,
+
+    /// Generation of slots added at the end of `vec`, newer than that of
+    /// any slot dropped from it.
+    generation: u32,
+
+    /// Opt-in index of the children of every node.
+    child_index: ChildIndex,
+
+    /// Version of the structure, checked by ancestry indexes.
+    version: Version,
 }
 
 /// Node ID.
 ///
-/// Index into a `Tree`-internal `Vec`.
+/// Index into a `Tree`-internal `Vec`, tagged with the generation of the slot
+/// it was created in. Slots of removed nodes are reused with a new generation,
+/// so an ID of a removed node never refers to the node that replaced it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct NodeId(NonZeroUsize);
+pub struct NodeId {
+    index: NonZeroU32,
+    generation: u32,
+}
 
 impl NodeId {
-    // Safety: `n` must not equal `usize::MAX`.
-    // (This is never the case for `Vec::len()`, that would mean it owns
-    // the entire address space without leaving space even for its pointer.)
-    unsafe fn from_index(n: usize) -> Self {
-        NodeId(NonZeroUsize::new_unchecked(n + 1))
+    // Safety: `n` must be less than `u32::MAX`.
+    unsafe fn from_index(n: usize, generation: u32) -> Self {
+        NodeId {
+            index: NonZeroU32::new_unchecked(n as u32 + 1),
+            generation,
+        }
+    }
+
+    // Largest index that can be represented by a node ID.
+    const MAX_INDEX: usize = u32::MAX as usize - 1;
+
+    // Panics if `n` cannot be represented by a node ID.
+    fn from_new_index(n: usize, generation: u32) -> Self {
+        assert!(n <= NodeId::MAX_INDEX, "Tree cannot hold more nodes");
+        unsafe { NodeId::from_index(n, generation) }
     }
 
     fn to_index(self) -> usize {
-        self.0.get() - 1
+        self.index.get() as usize - 1
+    }
+
+    // Safety: the index of the result must be less than `u32::MAX`.
+    unsafe fn offset(self, offset: usize, generation: u32) -> Self {
+        NodeId::from_index(
+            self.to_index() + offset,
+            self.generation.wrapping_add(generation),
+        )
+    }
+}
+
+/// Mapping from old to new node IDs.
+///
+/// Returned by operations that renumber nodes, such as [`Tree::compact`],
+/// so that IDs stored outside the tree can be translated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdMap {
+    /// Pairs of old and new IDs, sorted by old index, so that the map only
+    /// takes space for the nodes it maps.
+    pairs: Vec<(NodeId, NodeId)>,
+}
+
+impl IdMap {
+    fn from_pairs(mut pairs: Vec<(NodeId, NodeId)>) -> Self {
+        pairs.sort_unstable_by_key(|(old, _)| old.to_index());
+        IdMap { pairs }
+    }
+
+    /// Maps nodes to the IDs of the first slots of a new tree, in order.
+    fn numbering(ids: &[NodeId]) -> Self {
+        let pairs = ids.iter().enumerate();
+        IdMap::from_pairs(
+            pairs
+                .map(|(index, &id)| (id, unsafe { NodeId::from_index(index, 0) }))
+                .collect(),
+        )
+    }
+
+    /// Returns the new ID of a node, or `None` if the node was not kept.
+    ///
+    /// Runs in time logarithmic in the number of mapped nodes.
+    pub fn get(&self, old: NodeId) -> Option<NodeId> {
+        let index = self
+            .pairs
+            .binary_search_by_key(&old.to_index(), |(id, _)| id.to_index())
+            .ok()?;
+        let (id, new) = self.pairs[index];
+        (id == old).then_some(new)
+    }
+
+    /// Returns the number of mapped nodes.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns true if no nodes are mapped.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Returns an iterator over pairs of old and new IDs, ordered by old ID.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.pairs.iter().copied()
+    }
+}
+
+/// Error returned by the fallible `try_*` operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The ID does not refer to a node of the tree.
+    InvalidId(NodeId),
+
+    /// The node is an orphan, so it has no siblings.
+    Orphan,
+
+    /// The node would be moved relative to itself.
+    SelfReference,
+
+    /// The node would be moved under one of its own descendants.
+    WouldCycle,
+
+    /// The operation is not permitted on the root node.
+    Root,
+
+    /// The nodes do not form a contiguous range of children of the node.
+    InvalidRange,
+
+    /// The child index is greater than the number of children.
+    IndexOutOfBounds {
+        /// The requested index.
+        index: usize,
+        /// The number of children.
+        len: usize,
+    },
+}
+
+impl Error {
+    // Panics with a message naming the failed action, e.g. "remove node".
+    fn panic(self, action: &str) -> ! {
+        panic!("Cannot {action}: {self}")
+    }
+
+    // Panics with a message naming the failed action and the relation it
+    // would create between the nodes, e.g. "append node" "as a child to".
+    fn panic_relative(self, action: &str, relation: &str) -> ! {
+        match self {
+            Error::SelfReference => panic!("Cannot {action} {relation} itself"),
+            Error::WouldCycle => panic!("Cannot {action} {relation} its own descendant"),
+            err => err.panic(action),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidId(id) => write!(f, "{id:?} does not refer to a node of the tree"),
+            Error::Orphan => write!(f, "node is an orphan"),
+            Error::SelfReference => write!(f, "node cannot be moved relative to itself"),
+            Error::WouldCycle => write!(f, "node cannot be moved under its own descendant"),
+            Error::Root => write!(f, "operation is not permitted on the root node"),
+            Error::InvalidRange => write!(f, "nodes do not form a range of children"),
+            Error::IndexOutOfBounds { index, len } => {
+                write!(f, "child index {index} is out of bounds for {len} children")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Slot<T> {
+    Occupied {
+        generation: u32,
+        node: Node<T>,
+    },
+    Vacant {
+        generation: u32,
+        next_free: Option<NodeId>,
+    },
+}
+
+impl<T> Slot<T> {
+    fn generation(&self) -> u32 {
+        match *self {
+            Slot::Occupied { generation, .. } | Slot::Vacant { generation, .. } => generation,
+        }
+    }
+
+    fn node(&self) -> Option<&Node<T>> {
+        match self {
+            Slot::Occupied { node, .. } => Some(node),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    fn node_mut(&mut self) -> Option<&mut Node<T>> {
+        match self {
+            Slot::Occupied { node, .. } => Some(node),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    fn into_node(self) -> Option<Node<T>> {
+        match self {
+            Slot::Occupied { node, .. } => Some(node),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns the node in this slot if `id` refers to it.
+    fn get(&self, id: NodeId) -> Option<&Node<T>> {
+        match self {
+            Slot::Occupied { generation, node } if *generation == id.generation => Some(node),
+            _ => None,
+        }
+    }
+
+    fn map<F, U>(self, transform: F) -> Slot<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        match self {
+            Slot::Occupied { generation, node } => Slot::Occupied {
+                generation,
+                node: node.map(transform),
+            },
+            Slot::Vacant {
+                generation,
+                next_free,
+            } => Slot::Vacant {
+                generation,
+                next_free,
+            },
+        }
+    }
+
+    fn map_ref<F, U>(&self, transform: F) -> Slot<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        match self {
+            Slot::Occupied { generation, node } => Slot::Occupied {
+                generation: *generation,
+                node: node.map_ref(transform),
+            },
+            Slot::Vacant {
+                generation,
+                next_free,
+            } => Slot::Vacant {
+                generation: *generation,
+                next_free: *next_free,
+            },
+        }
     }
 }
 
@@ -74,6 +333,8 @@ struct Node<T> {
     prev_sibling: Option<NodeId>,
     next_sibling: Option<NodeId>,
     children: Option<(NodeId, NodeId)>,
+    /// Number of children, which fits in `u32` like the indices of node IDs.
+    child_count: u32,
     value: T,
 }
 
@@ -81,7 +342,7 @@ fn _static_assert_size_of_node() {
     // "Instantiating" the generic `transmute` function without calling it
     // still triggers the magic compile-time check
     // that input and output types have the same `size_of()`.
-    let _ = std::mem::transmute::<Node<()>, [usize; 5]>;
+    let _ = std::mem::transmute::<Node<()>, ([NodeId; 5], u32)>;
 }
 
 impl<T> Node<T> {
@@ -91,10 +352,31 @@ impl<T> Node<T> {
             prev_sibling: None,
             next_sibling: None,
             children: None,
+            child_count: 0,
             value,
         }
     }
 
+    fn remap<F>(&mut self, mut f: F)
+    where
+        F: FnMut(NodeId) -> NodeId,
+    {
+        for id in [
+            &mut self.parent,
+            &mut self.prev_sibling,
+            &mut self.next_sibling,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *id = f(*id);
+        }
+        if let Some((first_child_id, last_child_id)) = &mut self.children {
+            *first_child_id = f(*first_child_id);
+            *last_child_id = f(*last_child_id);
+        }
+    }
+
     pub fn map<F, U>(self, mut transform: F) -> Node<U>
     where
         F: FnMut(T) -> U,
@@ -104,6 +386,7 @@ impl<T> Node<T> {
             prev_sibling: self.prev_sibling,
             next_sibling: self.next_sibling,
             children: self.children,
+            child_count: self.child_count,
             value: transform(self.value),
         }
     }
@@ -117,6 +400,7 @@ impl<T> Node<T> {
             prev_sibling: self.prev_sibling,
             next_sibling: self.next_sibling,
             children: self.children,
+            child_count: self.child_count,
             value: transform(&self.value),
         }
     }
@@ -162,48 +446,389 @@ impl<'a, T: 'a> PartialEq for NodeRef<'a, T> {
     }
 }
 
+impl<T: PartialEq> PartialEq for Tree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root && self.occupied().eq(other.occupied())
+    }
+}
+impl<T: Eq> Eq for Tree<T> {}
+impl<T: Hash> Hash for Tree<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.root.hash(state);
+        self.len().hash(state);
+        for slot in self.occupied() {
+            slot.hash(state);
+        }
+    }
+}
+
 impl<T> Tree<T> {
     /// Creates a tree with a root node.
     pub fn new(root: T) -> Self {
-        Tree {
-            vec: vec![Node::new(root)],
-        }
+        Tree::from_nodes(vec![Node::new(root)])
     }
 
     /// Creates a tree with a root node and the specified capacity.
     pub fn with_capacity(root: T, capacity: usize) -> Self {
         let mut vec = Vec::with_capacity(capacity);
-        vec.push(Node::new(root));
-        Tree { vec }
+        vec.push(Slot::Occupied {
+            generation: 0,
+            node: Node::new(root),
+        });
+        Tree {
+            vec,
+            root: unsafe { NodeId::from_index(0, 0) },
+            free: None,
+            vacant: 0,
+            generation: 0,
+            child_index: ChildIndex::default(),
+            version: Version::default(),
+        }
+    }
+
+    /// Creates a tree from nodes numbered from zero, the first being the root.
+    fn from_nodes(nodes: Vec<Node<T>>) -> Self {
+        let vec = nodes
+            .into_iter()
+            .map(|node| Slot::Occupied {
+                generation: 0,
+                node,
+            })
+            .collect();
+        Tree {
+            vec,
+            root: unsafe { NodeId::from_index(0, 0) },
+            free: None,
+            vacant: 0,
+            generation: 0,
+            child_index: ChildIndex::default(),
+            version: Version::default(),
+        }
+    }
+
+    /// Returns the occupied slots with their indices.
+    fn occupied(&self) -> impl Iterator<Item = (usize, &Slot<T>)> {
+        self.vec
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.node().is_some())
+    }
+
+    /// Returns the number of nodes in the tree, including orphans.
+    // A tree always has a root, so it is never empty.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.vec.len() - self.vacant
+    }
+
+    /// Returns the number of nodes reachable from the root, including the root.
+    ///
+    /// This walks the tree, so it runs in linear time.
+    pub fn reachable_len(&self) -> usize {
+        self.root().descendants().count()
+    }
+
+    /// Returns the number of nodes not reachable from the root,
+    /// that is orphans and their descendants.
+    ///
+    /// This walks the tree, so it runs in linear time.
+    pub fn orphaned_len(&self) -> usize {
+        self.len() - self.reachable_len()
+    }
+
+    /// Returns the number of nodes the tree can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more nodes.
+    ///
+    /// Slots of removed nodes count towards the capacity.
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional.saturating_sub(self.vacant));
+    }
+
+    /// Reserves capacity for exactly `additional` more nodes.
+    ///
+    /// Slots of removed nodes count towards the capacity.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.vec
+            .reserve_exact(additional.saturating_sub(self.vacant));
+    }
+
+    /// Shrinks the capacity of the tree as much as possible.
+    ///
+    /// Slots of removed nodes are released only at the end of the tree's
+    /// storage. Use [`Tree::compact`] to release all of them.
+    pub fn shrink_to_fit(&mut self) {
+        let len = self
+            .vec
+            .iter()
+            .rposition(|slot| slot.node().is_some())
+            .map_or(0, |index| index + 1);
+        if len < self.vec.len() {
+            self.truncate(len);
+            self.rebuild_free_list();
+        }
+        self.vec.shrink_to_fit();
+    }
+
+    /// Removes all nodes except the root, keeping the allocated capacity.
+    ///
+    /// IDs of the removed nodes are stale afterwards, the ID of the root is not.
+    pub fn clear(&mut self) {
+        let root_index = self.root.to_index();
+        self.truncate(root_index + 1);
+        for slot in &mut self.vec[..root_index] {
+            if let Slot::Occupied { generation, .. } = *slot {
+                *slot = Slot::Vacant {
+                    generation: generation.wrapping_add(1),
+                    next_free: None,
+                };
+                self.vacant += 1;
+            }
+        }
+        self.rebuild_free_list();
+        let root = unsafe { self.node_mut(self.root) };
+        root.parent = None;
+        root.prev_sibling = None;
+        root.next_sibling = None;
+        root.children = None;
+        root.child_count = 0;
+        self.child_index.rebuild_range(&self.vec, 0);
+    }
+
+    /// Replaces the tree with a new tree consisting only of a root node,
+    /// keeping the allocated capacity.
+    ///
+    /// This is the same as [`Tree::clear`] followed by replacing the value of
+    /// the root.
+    pub fn reset(&mut self, root: T) {
+        self.clear();
+        *self.root_mut().value() = root;
+    }
+
+    /// Drops the slots from `len` on, keeping the IDs of their nodes stale.
+    ///
+    /// The free list must be rebuilt afterwards if it contained dropped slots.
+    fn truncate(&mut self, len: usize) {
+        for slot in &self.vec[len..] {
+            if slot.node().is_none() {
+                self.vacant -= 1;
+            }
+            self.generation = self.generation.max(slot.generation().wrapping_add(1));
+        }
+        self.vec.truncate(len);
+        self.child_index.truncate(len);
+        self.version.bump();
+    }
+
+    /// Links all vacant slots into the free list, lowest index first.
+    fn rebuild_free_list(&mut self) {
+        self.free = None;
+        for index in (0..self.vec.len()).rev() {
+            if let Slot::Vacant {
+                generation,
+                next_free,
+            } = &mut self.vec[index]
+            {
+                *next_free = self.free;
+                self.free = Some(unsafe { NodeId::from_index(index, *generation) });
+            }
+        }
     }
 
     /// Returns a reference to the specified node.
-    pub fn get(&self, id: NodeId) -> Option<NodeRef<T>> {
-        self.vec.get(id.to_index()).map(|node| NodeRef {
-            id,
-            node,
-            tree: self,
-        })
+    ///
+    /// Returns `None` if the node has been removed.
+    pub fn get(&self, id: NodeId) -> Option<NodeRef<'_, T>> {
+        self.vec
+            .get(id.to_index())
+            .and_then(|slot| slot.get(id))
+            .map(|node| NodeRef {
+                id,
+                node,
+                tree: self,
+            })
     }
 
     /// Returns a mutator of the specified node.
-    pub fn get_mut(&mut self, id: NodeId) -> Option<NodeMut<T>> {
-        let exists = self.vec.get(id.to_index()).map(|_| ());
+    ///
+    /// Returns `None` if the node has been removed.
+    pub fn get_mut(&mut self, id: NodeId) -> Option<NodeMut<'_, T>> {
+        let exists = self
+            .vec
+            .get(id.to_index())
+            .and_then(|slot| slot.get(id))
+            .map(|_| ());
         exists.map(move |_| NodeMut { id, tree: self })
     }
 
+    /// Returns the ID of the slot at `index`, whether or not it is occupied.
+    fn id_at(&self, index: usize) -> NodeId {
+        unsafe { NodeId::from_index(index, self.vec[index].generation()) }
+    }
+
+    /// Returns a node by ID.
+    ///
+    /// Still checks the slot, since a `NodeMut` can remove its own node
+    /// through [`NodeMut::tree`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node has been removed.
     unsafe fn node(&self, id: NodeId) -> &Node<T> {
-        self.vec.get_unchecked(id.to_index())
+        match self.vec.get(id.to_index()).and_then(|slot| slot.get(id)) {
+            Some(node) => node,
+            None => panic!("{}", Error::InvalidId(id)),
+        }
     }
 
+    /// Returns a node to change its links, bumping the version.
     unsafe fn node_mut(&mut self, id: NodeId) -> &mut Node<T> {
-        self.vec.get_unchecked_mut(id.to_index())
+        self.version.bump();
+        &mut *self.node_ptr(id)
+    }
+
+    unsafe fn value_mut(&mut self, id: NodeId) -> &mut T {
+        &mut (*self.node_ptr(id)).value
+    }
+
+    unsafe fn node_ptr(&mut self, id: NodeId) -> *mut Node<T> {
+        match self.vec.get_mut(id.to_index()) {
+            Some(Slot::Occupied { generation, node }) if *generation == id.generation => node,
+            _ => panic!("{}", Error::InvalidId(id)),
+        }
+    }
+
+    /// Returns true if `ancestor` is a proper ancestor of the node `id`.
+    ///
+    /// Runs in time proportional to the depth of the node.
+    unsafe fn has_ancestor(&self, id: NodeId, ancestor: NodeId) -> bool {
+        let mut parent = self.node(id).parent;
+        while let Some(parent_id) = parent {
+            if parent_id == ancestor {
+                return true;
+            }
+            parent = self.node(parent_id).parent;
+        }
+        false
+    }
+
+    /// Checks that `first` to `last` is a range of children of `parent`.
+    ///
+    /// Runs in time proportional to the length of the range.
+    fn check_range(&self, parent: NodeId, first: NodeId, last: NodeId) -> Result<(), Error> {
+        for id in [first, last] {
+            if self.get(id).ok_or(Error::InvalidId(id))?.node.parent != Some(parent) {
+                return Err(Error::InvalidRange);
+            }
+        }
+        let mut id = Some(first);
+        while let Some(sibling_id) = id {
+            if sibling_id == last {
+                return Ok(());
+            }
+            id = unsafe { self.node(sibling_id).next_sibling };
+        }
+        Err(Error::InvalidRange)
+    }
+
+    /// Unlinks the range of siblings `first` to `last` from its parent.
+    ///
+    /// The nodes keep their parent link and are still counted as its children
+    /// until linked again, the ends of the range are cleared.
+    unsafe fn unlink_range(&mut self, first: NodeId, last: NodeId) {
+        let parent_id = self.node(first).parent.unwrap();
+        let prev_sibling_id = self.node_mut(first).prev_sibling.take();
+        let next_sibling_id = self.node_mut(last).next_sibling.take();
+
+        if let Some(id) = prev_sibling_id {
+            self.node_mut(id).next_sibling = next_sibling_id;
+        }
+        if let Some(id) = next_sibling_id {
+            self.node_mut(id).prev_sibling = prev_sibling_id;
+        }
+
+        let parent = self.node_mut(parent_id);
+        let (first_child_id, last_child_id) = parent.children.unwrap();
+        parent.children = match (prev_sibling_id, next_sibling_id) {
+            (None, None) => None,
+            (None, Some(next_id)) => Some((next_id, last_child_id)),
+            (Some(prev_id), None) => Some((first_child_id, prev_id)),
+            (Some(_), Some(_)) => Some((first_child_id, last_child_id)),
+        };
+        self.child_index.rebuild(&self.vec, parent_id);
+    }
+
+    /// Links an unlinked range of siblings `first` to `last` under `parent`,
+    /// after `prev_sibling` or as the first children.
+    ///
+    /// Updates the parent link of every node of the range, and the child
+    /// counts of both parents, unless the parent is already `parent`.
+    unsafe fn link_range(
+        &mut self,
+        parent: NodeId,
+        prev_sibling: Option<NodeId>,
+        first: NodeId,
+        last: NodeId,
+    ) {
+        let old_parent = self.node(first).parent;
+        if old_parent != Some(parent) {
+            let mut len = 0;
+            let mut id = Some(first);
+            while let Some(sibling_id) = id {
+                let node = self.node_mut(sibling_id);
+                node.parent = Some(parent);
+                len += 1;
+                id = if sibling_id == last {
+                    None
+                } else {
+                    node.next_sibling
+                };
+            }
+            if let Some(old_parent) = old_parent {
+                self.node_mut(old_parent).child_count -= len;
+            }
+            self.node_mut(parent).child_count += len;
+        }
+
+        let next_sibling = match prev_sibling {
+            Some(id) => self.node(id).next_sibling,
+            None => self.node(parent).children.map(|(id, _)| id),
+        };
+        self.node_mut(first).prev_sibling = prev_sibling;
+        self.node_mut(last).next_sibling = next_sibling;
+        if let Some(id) = prev_sibling {
+            self.node_mut(id).next_sibling = Some(first);
+        }
+        if let Some(id) = next_sibling {
+            self.node_mut(id).prev_sibling = Some(last);
+        }
+
+        let parent_node = self.node_mut(parent);
+        parent_node.children = match parent_node.children {
+            None => Some((first, last)),
+            Some((first_child_id, last_child_id)) => Some((
+                if prev_sibling.is_none() {
+                    first
+                } else {
+                    first_child_id
+                },
+                if next_sibling.is_none() {
+                    last
+                } else {
+                    last_child_id
+                },
+            )),
+        };
+        self.child_index.rebuild(&self.vec, parent);
     }
 
     /// Returns a reference to the specified node.
     /// # Safety
     /// The caller must ensure that `id` is a valid node ID.
-    pub unsafe fn get_unchecked(&self, id: NodeId) -> NodeRef<T> {
+    pub unsafe fn get_unchecked(&self, id: NodeId) -> NodeRef<'_, T> {
         NodeRef {
             id,
             node: self.node(id),
@@ -214,49 +839,666 @@ impl<T> Tree<T> {
     /// Returns a mutator of the specified node.
     /// # Safety
     /// The caller must ensure that `id` is a valid node ID.
-    pub unsafe fn get_unchecked_mut(&mut self, id: NodeId) -> NodeMut<T> {
+    pub unsafe fn get_unchecked_mut(&mut self, id: NodeId) -> NodeMut<'_, T> {
         NodeMut { id, tree: self }
     }
 
     /// Returns a reference to the root node.
-    pub fn root(&self) -> NodeRef<T> {
-        unsafe { self.get_unchecked(NodeId::from_index(0)) }
+    pub fn root(&self) -> NodeRef<'_, T> {
+        unsafe { self.get_unchecked(self.root) }
     }
 
     /// Returns a mutator of the root node.
-    pub fn root_mut(&mut self) -> NodeMut<T> {
-        unsafe { self.get_unchecked_mut(NodeId::from_index(0)) }
+    pub fn root_mut(&mut self) -> NodeMut<'_, T> {
+        unsafe { self.get_unchecked_mut(self.root) }
+    }
+
+    /// Makes a node the root of the tree, returning the ID of the previous root.
+    ///
+    /// The node is detached from its parent first. The previous root and its
+    /// remaining descendants become orphans, which can be dropped with
+    /// [`Tree::remove`] or [`Tree::compact`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    /// let b = tree.root().first_child().unwrap().id();
+    /// let a = tree.set_root(b);
+    /// tree.remove(a);
+    /// assert_eq!(tree!('b' => { 'c' }).to_string(), tree.to_string());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not valid.
+    pub fn set_root(&mut self, id: NodeId) -> NodeId {
+        self.try_set_root(id)
+            .unwrap_or_else(|err| err.panic("set root to node"))
+    }
+
+    /// Makes a node the root of the tree, returning the ID of the previous root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidId`] if `id` is not valid.
+    pub fn try_set_root(&mut self, id: NodeId) -> Result<NodeId, Error> {
+        self.get_mut(id).ok_or(Error::InvalidId(id))?.detach();
+        self.version.bump();
+        Ok(std::mem::replace(&mut self.root, id))
+    }
+
+    /// Creates a new root node with the previous root as its only child.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('b' => { 'c' });
+    /// tree.wrap_root('a');
+    /// assert_eq!(tree!('a' => { 'b' => { 'c' } }).to_string(), tree.to_string());
+    /// ```
+    pub fn wrap_root(&mut self, value: T) -> NodeMut<'_, T> {
+        let old_root_id = self.root;
+        let id = self.orphan(value).id;
+        self.root = id;
+        let mut root = unsafe { self.get_unchecked_mut(id) };
+        root.append_id(old_root_id);
+        root
     }
 
     /// Creates an orphan node.
-    pub fn orphan(&mut self, value: T) -> NodeMut<T> {
-        let id = unsafe { NodeId::from_index(self.vec.len()) };
-        self.vec.push(Node::new(value));
+    ///
+    /// Reuses the slot of a removed node if there is one.
+    pub fn orphan(&mut self, value: T) -> NodeMut<'_, T> {
+        let id = match self.free {
+            Some(id) => {
+                let slot = unsafe { self.vec.get_unchecked_mut(id.to_index()) };
+                if let Slot::Vacant { next_free, .. } = *slot {
+                    self.free = next_free;
+                }
+                self.vacant -= 1;
+                *slot = Slot::Occupied {
+                    generation: id.generation,
+                    node: Node::new(value),
+                };
+                id
+            }
+            None => {
+                let id = NodeId::from_new_index(self.vec.len(), self.generation);
+                self.vec.push(Slot::Occupied {
+                    generation: id.generation,
+                    node: Node::new(value),
+                });
+                id
+            }
+        };
+        self.child_index.reset(id);
+        self.version.bump();
         unsafe { self.get_unchecked_mut(id) }
     }
 
+    /// Removes a node and its descendants, returning the value of the node.
+    ///
+    /// The node is detached first. The values of its descendants are dropped
+    /// and all of their slots are reused by nodes created later. IDs of
+    /// removed nodes are stale: `get` and `get_mut` return `None` for them.
+    ///
+    /// Returns `None` if `id` does not refer to a node of this tree, or if it
+    /// is the root node, which cannot be removed. See [`Tree::try_remove`].
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        self.try_remove(id).ok()
+    }
+
+    /// Removes a node and its descendants, returning the value of the node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::InvalidId`] if `id` does not refer to a node of this tree.
+    /// - Returns [`Error::Root`] if `id` is the root node.
+    pub fn try_remove(&mut self, id: NodeId) -> Result<T, Error> {
+        self.get_mut(id)
+            .ok_or(Error::InvalidId(id))?
+            .try_remove_subtree()
+    }
+
+    /// Frees the slot of a node, returning the node.
+    ///
+    /// The node must not be linked to any node that is still in use.
+    unsafe fn free(&mut self, id: NodeId) -> Node<T> {
+        // Generations wrap around after `u32::MAX` reuses of the same slot.
+        let generation = id.generation.wrapping_add(1);
+        let slot = std::mem::replace(
+            self.vec.get_unchecked_mut(id.to_index()),
+            Slot::Vacant {
+                generation,
+                next_free: self.free,
+            },
+        );
+        self.free = Some(NodeId::from_index(id.to_index(), generation));
+        self.vacant += 1;
+        self.child_index.reset(id);
+        self.version.bump();
+        match slot {
+            Slot::Occupied { node, .. } => node,
+            Slot::Vacant { .. } => std::hint::unreachable_unchecked(),
+        }
+    }
+
     /// Merge with another tree as orphan, returning the new root of tree being merged.
-    // Allowing this for compactness.
-    #[allow(clippy::option_map_unit_fn)]
-    pub fn extend_tree(&mut self, mut other_tree: Tree<T>) -> NodeMut<T> {
+    pub fn extend_tree(&mut self, other_tree: Tree<T>) -> NodeMut<'_, T> {
+        let other_tree_root_id = other_tree.root().id;
+        let offset_id = self.merge(other_tree);
+        unsafe { self.get_unchecked_mut(offset_id(other_tree_root_id)) }
+    }
+
+    /// Merge with another tree as orphan, returning the new root of tree being merged
+    /// and the mapping from the IDs of the other tree to their IDs in this tree.
+    ///
+    /// All nodes of the other tree are mapped, including its orphans.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a');
+    /// let other = tree!('b' => { 'c' });
+    /// let c = other.root().first_child().unwrap().id();
+    ///
+    /// let (_, map) = tree.extend_tree_mapped(other);
+    /// assert_eq!(&'c', tree.get(map.get(c).unwrap()).unwrap().value());
+    /// ```
+    pub fn extend_tree_mapped(&mut self, other_tree: Tree<T>) -> (NodeMut<'_, T>, IdMap) {
+        let other_tree_root_id = other_tree.root().id;
+        let ids = other_tree.nodes().map(|node| node.id).collect::<Vec<_>>();
+
+        let offset_id = self.merge(other_tree);
+        let map = IdMap::from_pairs(ids.into_iter().map(|id| (id, offset_id(id))).collect());
+
+        let root = unsafe { self.get_unchecked_mut(offset_id(other_tree_root_id)) };
+        (root, map)
+    }
+
+    /// Moves the slots of another tree to the end of this tree,
+    /// returning the function translating their IDs.
+    fn merge(&mut self, mut other_tree: Tree<T>) -> impl Fn(NodeId) -> NodeId {
         let offset = self.vec.len();
-        let offset_id = |id: NodeId| -> NodeId {
-            let old_index = id.to_index();
-            let new_index = old_index + offset;
-            unsafe { NodeId::from_index(new_index) }
+        let generation = self.generation;
+        assert!(
+            offset + other_tree.vec.len() - 1 <= NodeId::MAX_INDEX,
+            "Tree cannot hold more nodes"
+        );
+        let offset_id = move |id: NodeId| unsafe { id.offset(offset, generation) };
+
+        for (index, slot) in other_tree.vec.iter_mut().enumerate() {
+            let id = offset_id(unsafe { NodeId::from_index(index, slot.generation()) });
+            match slot {
+                Slot::Occupied {
+                    generation, node, ..
+                } => {
+                    *generation = id.generation;
+                    node.remap(offset_id);
+                }
+                Slot::Vacant {
+                    generation,
+                    next_free,
+                } => {
+                    *generation = id.generation;
+                    *next_free = self.free;
+                    self.free = Some(id);
+                }
+            }
+        }
+        self.vec.extend(other_tree.vec);
+        self.vacant += other_tree.vacant;
+        self.child_index.rebuild_range(&self.vec, offset);
+        self.version.bump();
+        offset_id
+    }
+
+    /// Moves a node and its descendants from another tree, appending it to
+    /// `dest_parent` in this tree.
+    ///
+    /// Returns the new ID of the moved node and the mapping from the IDs of
+    /// the moved nodes in `src` to their IDs in this tree. The slots of the
+    /// moved nodes in `src` are freed, as with [`Tree::remove`]. Renumbering
+    /// the moved nodes runs in `O(n log n)` time in the size of the subtree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut src = tree!('a' => { 'b' => { 'c' } });
+    /// let mut dest = tree!('x' => { 'y' });
+    /// let b = src.root().first_child().unwrap().id();
+    /// let c = src.get(b).unwrap().first_child().unwrap().id();
+    /// let y = dest.root().first_child().unwrap().id();
+    ///
+    /// let (new_b, map) = dest.transplant(&mut src, b, y);
+    /// assert_eq!(&'b', dest.get(new_b).unwrap().value());
+    /// assert_eq!(&'c', dest.get(map.get(c).unwrap()).unwrap().value());
+    /// assert_eq!(tree!('a'), src);
+    /// assert_eq!(tree!('x' => { 'y' => { 'b' => { 'c' } } }), dest);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `src_id` is not valid in `src`.
+    /// - Panics if `src_id` is the root node of `src`.
+    /// - Panics if `dest_parent` is not valid in this tree.
+    pub fn transplant(
+        &mut self,
+        src: &mut Tree<T>,
+        src_id: NodeId,
+        dest_parent: NodeId,
+    ) -> (NodeId, IdMap) {
+        self.try_transplant(src, src_id, dest_parent)
+            .unwrap_or_else(|err| err.panic("transplant node"))
+    }
+
+    /// Moves a node and its descendants from another tree, appending it to
+    /// `dest_parent` in this tree.
+    ///
+    /// Neither tree is modified if an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::InvalidId`] if `src_id` is not valid in `src` or
+    ///   `dest_parent` is not valid in this tree.
+    /// - Returns [`Error::Root`] if `src_id` is the root node of `src`.
+    pub fn try_transplant(
+        &mut self,
+        src: &mut Tree<T>,
+        src_id: NodeId,
+        dest_parent: NodeId,
+    ) -> Result<(NodeId, IdMap), Error> {
+        if self.get(dest_parent).is_none() {
+            return Err(Error::InvalidId(dest_parent));
+        }
+        let mut src_node = src.get_mut(src_id).ok_or(Error::InvalidId(src_id))?;
+        if src_id == src_node.tree.root().id {
+            return Err(Error::Root);
+        }
+        let (subtree, subtree_map) = src_node.take_subtree();
+
+        let (root, merge_map) = self.extend_tree_mapped(subtree);
+        let root_id = root.id;
+        let map = IdMap::from_pairs(
+            subtree_map
+                .iter()
+                .map(|(old_id, id)| (old_id, merge_map.get(id).unwrap()))
+                .collect(),
+        );
+
+        unsafe { self.get_unchecked_mut(dest_parent) }.append_id(root_id);
+        Ok((root_id, map))
+    }
+
+    /// Exchanges the positions of two nodes, moving their subtrees with them.
+    ///
+    /// The nodes may be siblings, adjacent or not, or live in different parts
+    /// of the tree. Swapping the root makes the other node the root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' => { 'e' } });
+    /// let c = tree.root().first_child().unwrap().first_child().unwrap().id();
+    /// let d = tree.root().last_child().unwrap().id();
+    /// tree.swap_nodes(c, d);
+    /// assert_eq!(
+    ///     tree!('a' => { 'b' => { 'd' => { 'e' } }, 'c' }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `a` or `b` is not valid.
+    /// - Panics if one node is an ancestor of the other.
+    pub fn swap_nodes(&mut self, a: NodeId, b: NodeId) {
+        self.try_swap_nodes(a, b)
+            .unwrap_or_else(|err| err.panic_relative("swap node", "with"))
+    }
+
+    /// Exchanges the positions of two nodes, moving their subtrees with them.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::InvalidId`] if `a` or `b` is not valid.
+    /// - Returns [`Error::WouldCycle`] if one node is an ancestor of the other.
+    pub fn try_swap_nodes(&mut self, a: NodeId, b: NodeId) -> Result<(), Error> {
+        for id in [a, b] {
+            self.get(id).ok_or(Error::InvalidId(id))?;
+        }
+        if a == b {
+            return Ok(());
+        }
+        if unsafe { self.has_ancestor(a, b) || self.has_ancestor(b, a) } {
+            return Err(Error::WouldCycle);
+        }
+
+        unsafe {
+            let node_a = self.node_mut(a);
+            let links_a = (node_a.parent, node_a.prev_sibling, node_a.next_sibling);
+            let node_b = self.node_mut(b);
+            let links_b = (node_b.parent, node_b.prev_sibling, node_b.next_sibling);
+
+            // Adjacent siblings would end up linked to themselves.
+            let swap = |id: Option<NodeId>| match id {
+                Some(id) if id == a => Some(b),
+                Some(id) if id == b => Some(a),
+                id => id,
+            };
+            for (id, (parent, prev_sibling, next_sibling)) in [(a, links_b), (b, links_a)] {
+                let node = self.node_mut(id);
+                node.parent = parent;
+                node.prev_sibling = swap(prev_sibling);
+                node.next_sibling = swap(next_sibling);
+            }
+
+            for id in [a, b] {
+                let node = self.node(id);
+                let (parent, prev_sibling, next_sibling) =
+                    (node.parent, node.prev_sibling, node.next_sibling);
+                match (prev_sibling, parent) {
+                    (Some(prev_id), _) => self.node_mut(prev_id).next_sibling = Some(id),
+                    (None, Some(parent_id)) => {
+                        let children = self.node_mut(parent_id).children.as_mut().unwrap();
+                        children.0 = id;
+                    }
+                    (None, None) => {}
+                }
+                match (next_sibling, parent) {
+                    (Some(next_id), _) => self.node_mut(next_id).prev_sibling = Some(id),
+                    (None, Some(parent_id)) => {
+                        let children = self.node_mut(parent_id).children.as_mut().unwrap();
+                        children.1 = id;
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+
+        if self.root == a {
+            self.root = b;
+        } else if self.root == b {
+            self.root = a;
+        }
+
+        for id in [a, b] {
+            match unsafe { self.node(id).parent } {
+                Some(parent_id) => self.child_index.rebuild(&self.vec, parent_id),
+                None => self.child_index.reset_position(id),
+            }
+        }
+        Ok(())
+    }
+
+    /// Exchanges the values of two nodes, leaving the structure unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    /// let c = tree.root().first_child().unwrap().first_child().unwrap().id();
+    /// let d = tree.root().last_child().unwrap().id();
+    /// tree.swap_values(c, d);
+    /// assert_eq!(tree!('a' => { 'b' => { 'd' }, 'c' }), tree);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `a` or `b` is not valid.
+    /// - Panics if one node is an ancestor of the other.
+    pub fn swap_values(&mut self, a: NodeId, b: NodeId) {
+        self.try_swap_values(a, b)
+            .unwrap_or_else(|err| err.panic_relative("swap value", "with"))
+    }
+
+    /// Exchanges the values of two nodes, leaving the structure unchanged.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::InvalidId`] if `a` or `b` is not valid.
+    /// - Returns [`Error::WouldCycle`] if one node is an ancestor of the other.
+    pub fn try_swap_values(&mut self, a: NodeId, b: NodeId) -> Result<(), Error> {
+        for id in [a, b] {
+            self.get(id).ok_or(Error::InvalidId(id))?;
+        }
+        if unsafe { self.has_ancestor(a, b) || self.has_ancestor(b, a) } {
+            return Err(Error::WouldCycle);
+        }
+        if a != b {
+            let (low, high) = (
+                a.to_index().min(b.to_index()),
+                a.to_index().max(b.to_index()),
+            );
+            let (head, tail) = self.vec.split_at_mut(high);
+            let low = head[low].node_mut().unwrap();
+            let high = tail[0].node_mut().unwrap();
+            std::mem::swap(&mut low.value, &mut high.value);
+        }
+        Ok(())
+    }
+
+    /// Moves the siblings from `first_id` to `last_id`, inclusive, to position
+    /// `index` among the children of `new_parent_id`, keeping their order.
+    ///
+    /// The index is the position of `first_id` once moved, counting only the
+    /// children of the new parent that are not part of the range.
+    ///
+    /// Runs in time linear in `index`, in the length of the range and in the
+    /// depth of the new parent. The parent links of the moved nodes are only
+    /// updated when the parent changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd', 'e' => { 'f' } });
+    /// let c = tree.root().first_child().unwrap().next_sibling().unwrap().id();
+    /// let d = tree.get(c).unwrap().next_sibling().unwrap().id();
+    /// let e = tree.root().last_child().unwrap().id();
+    /// tree.move_sibling_range(c, d, e, 1);
+    /// assert_eq!(
+    ///     tree!('a' => { 'b', 'e' => { 'f', 'c', 'd' } }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Panics if any of the IDs is not valid.
+    /// - Panics if `first_id` is an orphan.
+    /// - Panics if `first_id` to `last_id` is not a range of siblings.
+    /// - Panics if `new_parent_id` is one of the siblings or one of their descendants.
+    /// - Panics if `index` is greater than the number of other children of the new parent.
+    pub fn move_sibling_range(
+        &mut self,
+        first_id: NodeId,
+        last_id: NodeId,
+        new_parent_id: NodeId,
+        index: usize,
+    ) {
+        self.try_move_sibling_range(first_id, last_id, new_parent_id, index)
+            .unwrap_or_else(|err| err.panic_relative("move nodes", "under"))
+    }
+
+    /// Moves the siblings from `first_id` to `last_id`, inclusive, to position
+    /// `index` among the children of `new_parent_id`, keeping their order.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::InvalidId`] if any of the IDs is not valid.
+    /// - Returns [`Error::Orphan`] if `first_id` is an orphan.
+    /// - Returns [`Error::InvalidRange`] if `first_id` to `last_id` is not a
+    ///   range of siblings, in that order.
+    /// - Returns [`Error::SelfReference`] if `new_parent_id` is one of the siblings.
+    /// - Returns [`Error::WouldCycle`] if `new_parent_id` is a descendant of one of the siblings.
+    /// - Returns [`Error::IndexOutOfBounds`] if `index` is greater than the number
+    ///   of other children of the new parent.
+    pub fn try_move_sibling_range(
+        &mut self,
+        first_id: NodeId,
+        last_id: NodeId,
+        new_parent_id: NodeId,
+        index: usize,
+    ) -> Result<(), Error> {
+        let parent_id = self
+            .get(first_id)
+            .ok_or(Error::InvalidId(first_id))?
+            .node
+            .parent
+            .ok_or(Error::Orphan)?;
+        self.check_range(parent_id, first_id, last_id)?;
+        let new_parent = self
+            .get(new_parent_id)
+            .ok_or(Error::InvalidId(new_parent_id))?;
+
+        let in_range = |id: NodeId| {
+            let mut sibling = self.get(first_id);
+            while let Some(node) = sibling {
+                if node.id == id {
+                    return true;
+                }
+                sibling = if node.id == last_id {
+                    None
+                } else {
+                    node.next_sibling()
+                };
+            }
+            false
         };
-        let other_tree_root_id = offset_id(other_tree.root().id);
-        for node in other_tree.vec.iter_mut() {
-            node.parent.as_mut().map(|id| *id = offset_id(*id));
-            node.prev_sibling.as_mut().map(|id| *id = offset_id(*id));
-            node.next_sibling.as_mut().map(|id| *id = offset_id(*id));
-            node.children.as_mut().map(|(id1, id2)| {
-                *id1 = offset_id(*id1);
-                *id2 = offset_id(*id2);
+        let mut ancestor = Some(new_parent);
+        while let Some(node) = ancestor {
+            if node.node.parent == Some(parent_id) && in_range(node.id) {
+                return Err(if node.id == new_parent_id {
+                    Error::SelfReference
+                } else {
+                    Error::WouldCycle
+                });
+            }
+            ancestor = node.parent();
+        }
+
+        // Find the child to insert after, skipping the range itself, which
+        // also checks the index without counting every child.
+        let mut prev_sibling_id = None;
+        let mut len = 0;
+        let mut child = new_parent.first_child();
+        while len < index {
+            let node = child.ok_or(Error::IndexOutOfBounds { index, len })?;
+            if node.id == first_id {
+                child = unsafe { self.get_unchecked(last_id) }.next_sibling();
+                continue;
+            }
+            prev_sibling_id = Some(node.id);
+            len += 1;
+            child = node.next_sibling();
+        }
+
+        unsafe {
+            self.unlink_range(first_id, last_id);
+            self.link_range(new_parent_id, prev_sibling_id, first_id, last_id);
+        }
+        Ok(())
+    }
+
+    /// Drops every node that is not reachable from the root and renumbers the
+    /// remaining nodes in tree order, returning the mapping of their IDs.
+    ///
+    /// Removed slots are released as well. IDs of nodes that changed position
+    /// become stale and must be translated with the returned [`IdMap`].
+    /// Runs in time linear in the number of slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c' });
+    /// let b = tree.root().first_child().unwrap().id();
+    /// let c = tree.root().last_child().unwrap().id();
+    /// tree.get_mut(b).unwrap().detach();
+    ///
+    /// let map = tree.compact();
+    /// assert_eq!(None, map.get(b));
+    /// assert_eq!(&'c', tree.get(map.get(c).unwrap()).unwrap().value());
+    /// assert_eq!(vec![&'a', &'c'], tree.values().collect::<Vec<_>>());
+    /// ```
+    pub fn compact(&mut self) -> IdMap {
+        let order = self
+            .root()
+            .descendants()
+            .map(|node| node.id)
+            .collect::<Vec<_>>();
+
+        // New IDs by old index, for remapping the links in constant time.
+        let mut new_ids = vec![None; self.vec.len()];
+        for (index, &old_id) in order.iter().enumerate() {
+            // Nodes moving into a slot get a new generation, so that no old ID
+            // of that slot refers to them.
+            let generation = if old_id.to_index() == index {
+                old_id.generation
+            } else {
+                self.vec[index].generation().wrapping_add(1)
+            };
+            new_ids[old_id.to_index()] = Some(unsafe { NodeId::from_index(index, generation) });
+        }
+        let new_id = |id: NodeId| new_ids[id.to_index()].unwrap();
+        // The table is indexed by old index, so the pairs come out sorted.
+        let pairs = new_ids
+            .iter()
+            .enumerate()
+            .filter_map(|(index, new)| new.map(|new| (self.id_at(index), new)))
+            .collect();
+        let map = IdMap { pairs };
+
+        for slot in &self.vec[order.len()..] {
+            self.generation = self.generation.max(slot.generation().wrapping_add(1));
+        }
+
+        let mut old_vec = std::mem::take(&mut self.vec);
+        self.vec.reserve_exact(order.len());
+        let root_id = self.root;
+        for old_id in order {
+            let slot = std::mem::replace(
+                &mut old_vec[old_id.to_index()],
+                Slot::Vacant {
+                    generation: 0,
+                    next_free: None,
+                },
+            );
+            let mut node = slot.into_node().unwrap();
+            if old_id == root_id {
+                node.parent = None;
+                node.prev_sibling = None;
+                node.next_sibling = None;
+            }
+            node.remap(new_id);
+            self.vec.push(Slot::Occupied {
+                generation: new_id(old_id).generation,
+                node,
             });
         }
-        self.vec.extend(other_tree.vec);
-        unsafe { self.get_unchecked_mut(other_tree_root_id) }
+        self.root = new_id(self.root);
+        self.free = None;
+        self.vacant = 0;
+        self.child_index.rebuild_range(&self.vec, 0);
+        self.version.bump();
+
+        map
     }
 
     /// Maps a `Tree<T>` to `Tree<U>` by applying a function to all node values,
@@ -269,8 +1511,14 @@ impl<T> Tree<T> {
             vec: self
                 .vec
                 .into_iter()
-                .map(|node| node.map(&mut transform))
+                .map(|slot| slot.map(&mut transform))
                 .collect(),
+            root: self.root,
+            free: self.free,
+            vacant: self.vacant,
+            generation: self.generation,
+            child_index: self.child_index.clone(),
+            version: self.version,
         }
     }
 
@@ -284,8 +1532,14 @@ impl<T> Tree<T> {
             vec: self
                 .vec
                 .iter()
-                .map(|node| node.map_ref(&mut transform))
+                .map(|slot| slot.map_ref(&mut transform))
                 .collect(),
+            root: self.root,
+            free: self.free,
+            vacant: self.vacant,
+            generation: self.generation,
+            child_index: self.child_index.clone(),
+            version: self.version.clone(),
         }
     }
 }
@@ -347,6 +1601,120 @@ impl<'a, T: 'a> NodeRef<'a, T> {
     pub fn has_children(&self) -> bool {
         self.node.children.is_some()
     }
+
+    /// Returns true if this node has no children.
+    pub fn is_leaf(&self) -> bool {
+        !self.has_children()
+    }
+
+    /// Returns true if this node is the root of the tree.
+    pub fn is_root(&self) -> bool {
+        self.id == self.tree.root
+    }
+
+    /// Returns true if this node is an orphan, that is neither the root nor
+    /// the child of another node.
+    pub fn is_orphan(&self) -> bool {
+        self.node.parent.is_none() && !self.is_root()
+    }
+
+    /// Returns true if this node is the first child of its parent.
+    ///
+    /// Returns false for the root and orphans.
+    pub fn is_first_child(&self) -> bool {
+        self.node.parent.is_some() && self.node.prev_sibling.is_none()
+    }
+
+    /// Returns true if this node is the last child of its parent.
+    ///
+    /// Returns false for the root and orphans.
+    pub fn is_last_child(&self) -> bool {
+        self.node.parent.is_some() && self.node.next_sibling.is_none()
+    }
+
+    /// Returns the number of ancestors of this node.
+    ///
+    /// The root and orphans have depth zero. Runs in time linear in the depth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c' } });
+    /// let c = tree.root().first_child().unwrap().first_child().unwrap();
+    /// assert_eq!(2, c.depth());
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.ancestors().count()
+    }
+
+    /// Returns the number of edges on the longest path from this node down
+    /// to one of its descendants.
+    ///
+    /// Leaves have height zero. Runs in time linear in the size of the
+    /// subtree, without recursion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    /// assert_eq!(2, tree.root().height());
+    /// ```
+    pub fn height(&self) -> usize {
+        use crate::iter::Edge;
+        let mut depth = 0;
+        let mut height = 0;
+        for edge in self.traverse() {
+            match edge {
+                Edge::Open(_) => {
+                    height = height.max(depth);
+                    depth += 1;
+                }
+                Edge::Close(_) => depth -= 1,
+            }
+        }
+        height
+    }
+
+    /// Clones this node and its descendants into a new tree rooted at this node.
+    ///
+    /// The nodes are numbered in tree order. Orphans of the original tree
+    /// are not copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    /// let b = tree.root().first_child().unwrap();
+    /// assert_eq!(tree!('b' => { 'c', 'd' }), b.to_tree());
+    /// ```
+    pub fn to_tree(&self) -> Tree<T>
+    where
+        T: Clone,
+    {
+        let ids = self.descendants().map(|node| node.id).collect::<Vec<_>>();
+        let map = IdMap::numbering(&ids);
+
+        let nodes = ids
+            .iter()
+            .map(|&id| {
+                let mut node = unsafe { self.tree.node(id) }.map_ref(T::clone);
+                if id == self.id {
+                    node.parent = None;
+                    node.prev_sibling = None;
+                    node.next_sibling = None;
+                }
+                node.remap(|id| map.get(id).unwrap());
+                node
+            })
+            .collect();
+        Tree::from_nodes(nodes)
+    }
 }
 
 impl<'a, T: 'a> NodeMut<'a, T> {
@@ -366,22 +1734,22 @@ impl<'a, T: 'a> NodeMut<'a, T> {
 
     /// Returns the value of this node.
     pub fn value(&mut self) -> &mut T {
-        &mut self.node().value
+        unsafe { self.tree.value_mut(self.id) }
     }
 
-    fn axis<F>(&mut self, f: F) -> Option<NodeMut<T>>
+    fn axis<F>(&mut self, f: F) -> Option<NodeMut<'_, T>>
     where
-        F: FnOnce(&mut Node<T>) -> Option<NodeId>,
+        F: FnOnce(&Node<T>) -> Option<NodeId>,
     {
-        let id = f(self.node());
+        let id = f(unsafe { self.tree.node(self.id) });
         id.map(move |id| unsafe { self.tree.get_unchecked_mut(id) })
     }
 
-    fn into_axis<F>(mut self, f: F) -> Result<Self, Self>
+    fn into_axis<F>(self, f: F) -> Result<Self, Self>
     where
-        F: FnOnce(&mut Node<T>) -> Option<NodeId>,
+        F: FnOnce(&Node<T>) -> Option<NodeId>,
     {
-        let id = f(self.node());
+        let id = f(unsafe { self.tree.node(self.id) });
         match id {
             Some(id) => Ok(unsafe { self.tree.get_unchecked_mut(id) }),
             None => Err(self),
@@ -389,7 +1757,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     }
 
     /// Returns the parent of this node.
-    pub fn parent(&mut self) -> Option<NodeMut<T>> {
+    pub fn parent(&mut self) -> Option<NodeMut<'_, T>> {
         self.axis(|node| node.parent)
     }
 
@@ -402,7 +1770,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     }
 
     /// Returns the previous sibling of this node.
-    pub fn prev_sibling(&mut self) -> Option<NodeMut<T>> {
+    pub fn prev_sibling(&mut self) -> Option<NodeMut<'_, T>> {
         self.axis(|node| node.prev_sibling)
     }
 
@@ -415,7 +1783,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     }
 
     /// Returns the next sibling of this node.
-    pub fn next_sibling(&mut self) -> Option<NodeMut<T>> {
+    pub fn next_sibling(&mut self) -> Option<NodeMut<'_, T>> {
         self.axis(|node| node.next_sibling)
     }
 
@@ -428,7 +1796,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     }
 
     /// Returns the first child of this node.
-    pub fn first_child(&mut self) -> Option<NodeMut<T>> {
+    pub fn first_child(&mut self) -> Option<NodeMut<'_, T>> {
         self.axis(|node| node.children.map(|(id, _)| id))
     }
 
@@ -441,7 +1809,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     }
 
     /// Returns the last child of this node.
-    pub fn last_child(&mut self) -> Option<NodeMut<T>> {
+    pub fn last_child(&mut self) -> Option<NodeMut<'_, T>> {
         self.axis(|node| node.children.map(|(_, id)| id))
     }
 
@@ -453,58 +1821,232 @@ impl<'a, T: 'a> NodeMut<'a, T> {
         self.into_axis(|node| node.children.map(|(_, id)| id))
     }
 
-    /// Returns true if this node has siblings.
-    pub fn has_siblings(&self) -> bool {
-        unsafe { self.tree.get_unchecked(self.id).has_siblings() }
+    /// Returns true if this node has siblings.
+    pub fn has_siblings(&self) -> bool {
+        unsafe { self.tree.get_unchecked(self.id).has_siblings() }
+    }
+
+    /// Returns true if this node has children.
+    pub fn has_children(&self) -> bool {
+        unsafe { self.tree.get_unchecked(self.id).has_children() }
+    }
+
+    /// Returns true if this node has no children.
+    pub fn is_leaf(&self) -> bool {
+        !self.has_children()
+    }
+
+    /// Returns true if this node is the root of the tree.
+    pub fn is_root(&self) -> bool {
+        self.id == self.tree.root
+    }
+
+    /// Returns true if this node is an orphan, that is neither the root nor
+    /// the child of another node.
+    ///
+    /// See [`NodeRef::is_orphan`].
+    pub fn is_orphan(&self) -> bool {
+        unsafe { self.tree.get_unchecked(self.id).is_orphan() }
+    }
+
+    /// Returns true if this node is the first child of its parent.
+    ///
+    /// See [`NodeRef::is_first_child`].
+    pub fn is_first_child(&self) -> bool {
+        unsafe { self.tree.get_unchecked(self.id).is_first_child() }
+    }
+
+    /// Returns true if this node is the last child of its parent.
+    ///
+    /// See [`NodeRef::is_last_child`].
+    pub fn is_last_child(&self) -> bool {
+        unsafe { self.tree.get_unchecked(self.id).is_last_child() }
+    }
+
+    /// Returns the number of ancestors of this node.
+    ///
+    /// See [`NodeRef::depth`].
+    pub fn depth(&self) -> usize {
+        unsafe { self.tree.get_unchecked(self.id).depth() }
     }
 
-    /// Returns true if this node has children.
-    pub fn has_children(&self) -> bool {
-        unsafe { self.tree.get_unchecked(self.id).has_children() }
+    /// Returns the number of edges on the longest path from this node down
+    /// to one of its descendants.
+    ///
+    /// See [`NodeRef::height`].
+    pub fn height(&self) -> usize {
+        unsafe { self.tree.get_unchecked(self.id).height() }
     }
 
     /// Appends a new child to this node.
-    pub fn append(&mut self, value: T) -> NodeMut<T> {
+    pub fn append(&mut self, value: T) -> NodeMut<'_, T> {
         let id = self.tree.orphan(value).id;
         self.append_id(id)
     }
 
     /// Prepends a new child to this node.
-    pub fn prepend(&mut self, value: T) -> NodeMut<T> {
+    pub fn prepend(&mut self, value: T) -> NodeMut<'_, T> {
         let id = self.tree.orphan(value).id;
         self.prepend_id(id)
     }
 
+    /// Appends new children to this node, linking them in a single pass.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b' });
+    /// tree.root_mut().extend_children(['c', 'd']);
+    /// assert_eq!(tree!('a' => { 'b', 'c', 'd' }), tree);
+    /// ```
+    pub fn extend_children<I>(&mut self, values: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let values = values.into_iter();
+        self.tree.reserve(values.size_hint().0);
+
+        let children = self.node().children;
+        let mut first_id = children.map(|(id, _)| id);
+        let mut last_id = children.map(|(_, id)| id);
+        for value in values {
+            let id = self.tree.orphan(value).id;
+            let node = unsafe { self.tree.node_mut(id) };
+            node.parent = Some(self.id);
+            node.prev_sibling = last_id;
+            if let Some(last_id) = last_id {
+                unsafe {
+                    self.tree.node_mut(last_id).next_sibling = Some(id);
+                }
+            }
+            self.tree.child_index.push(self.id, id);
+            // Link every child right away, so that the tree stays valid if
+            // the iterator panics.
+            let first_id = *first_id.get_or_insert(id);
+            let parent = self.node();
+            parent.children = Some((first_id, id));
+            parent.child_count += 1;
+            last_id = Some(id);
+        }
+    }
+
     /// Appends a subtree, return the root of the merged subtree.
-    pub fn append_subtree(&mut self, subtree: Tree<T>) -> NodeMut<T> {
+    pub fn append_subtree(&mut self, subtree: Tree<T>) -> NodeMut<'_, T> {
         let root_id = self.tree.extend_tree(subtree).id;
         self.append_id(root_id)
     }
 
     /// Prepends a subtree, return the root of the merged subtree.
-    pub fn prepend_subtree(&mut self, subtree: Tree<T>) -> NodeMut<T> {
+    pub fn prepend_subtree(&mut self, subtree: Tree<T>) -> NodeMut<'_, T> {
         let root_id = self.tree.extend_tree(subtree).id;
         self.prepend_id(root_id)
     }
 
+    /// Appends a subtree, return the root of the merged subtree and the mapping
+    /// from the IDs of the subtree to their IDs in this tree.
+    ///
+    /// See [`Tree::extend_tree_mapped`].
+    pub fn append_subtree_mapped(&mut self, subtree: Tree<T>) -> (NodeMut<'_, T>, IdMap) {
+        let (root, map) = self.tree.extend_tree_mapped(subtree);
+        let root_id = root.id;
+        (self.append_id(root_id), map)
+    }
+
+    /// Prepends a subtree, return the root of the merged subtree and the mapping
+    /// from the IDs of the subtree to their IDs in this tree.
+    ///
+    /// See [`Tree::extend_tree_mapped`].
+    pub fn prepend_subtree_mapped(&mut self, subtree: Tree<T>) -> (NodeMut<'_, T>, IdMap) {
+        let (root, map) = self.tree.extend_tree_mapped(subtree);
+        let root_id = root.id;
+        (self.prepend_id(root_id), map)
+    }
+
+    /// Appends a deep copy of a node and its descendants of the same tree,
+    /// returning the root of the copy.
+    ///
+    /// The copied node may be this node or one of its ancestors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not valid.
+    pub fn append_copy_of(&mut self, id: NodeId) -> NodeMut<'_, T>
+    where
+        T: Clone,
+    {
+        self.try_append_copy_of(id)
+            .unwrap_or_else(|err| err.panic("append copy of node"))
+    }
+
+    /// Appends a deep copy of a node and its descendants of the same tree,
+    /// returning the root of the copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidId`] if `id` is not valid.
+    pub fn try_append_copy_of(&mut self, id: NodeId) -> Result<NodeMut<'_, T>, Error>
+    where
+        T: Clone,
+    {
+        let copy = self.tree.get(id).ok_or(Error::InvalidId(id))?.to_tree();
+        Ok(self.append_subtree(copy))
+    }
+
     /// Inserts a new sibling before this node.
     ///
     /// # Panics
     ///
-    /// Panics if this node is an orphan.
-    pub fn insert_before(&mut self, value: T) -> NodeMut<T> {
+    /// Panics if this node is the root node or an orphan.
+    pub fn insert_before(&mut self, value: T) -> NodeMut<'_, T> {
+        self.try_insert_before(value)
+            .unwrap_or_else(|err| err.panic("insert sibling"))
+    }
+
+    /// Inserts a new sibling before this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::Root`] if this node is the root node.
+    /// - Returns [`Error::Orphan`] if this node is an orphan.
+    pub fn try_insert_before(&mut self, value: T) -> Result<NodeMut<'_, T>, Error> {
+        self.sibling_parent()?;
         let id = self.tree.orphan(value).id;
-        self.insert_id_before(id)
+        self.try_insert_id_before(id)
     }
 
     /// Inserts a new sibling after this node.
     ///
     /// # Panics
     ///
-    /// Panics if this node is an orphan.
-    pub fn insert_after(&mut self, value: T) -> NodeMut<T> {
+    /// Panics if this node is the root node or an orphan.
+    pub fn insert_after(&mut self, value: T) -> NodeMut<'_, T> {
+        self.try_insert_after(value)
+            .unwrap_or_else(|err| err.panic("insert sibling"))
+    }
+
+    /// Inserts a new sibling after this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::Root`] if this node is the root node.
+    /// - Returns [`Error::Orphan`] if this node is an orphan.
+    pub fn try_insert_after(&mut self, value: T) -> Result<NodeMut<'_, T>, Error> {
+        self.sibling_parent()?;
         let id = self.tree.orphan(value).id;
-        self.insert_id_after(id)
+        self.try_insert_id_after(id)
+    }
+
+    /// Returns the parent of this node, which siblings are inserted under.
+    ///
+    /// The root node has no parent and cannot get siblings, unlike orphans
+    /// which may get a parent later.
+    fn sibling_parent(&mut self) -> Result<NodeId, Error> {
+        if self.id == self.tree.root {
+            return Err(Error::Root);
+        }
+        self.node().parent.ok_or(Error::Orphan)
     }
 
     /// Detaches this node from its parent.
@@ -513,6 +2055,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
             Some(id) => id,
             None => return,
         };
+        self.tree.child_index.remove(parent_id, self.id);
         let prev_sibling_id = self.node().prev_sibling;
         let next_sibling_id = self.node().next_sibling;
 
@@ -534,6 +2077,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
         }
 
         let parent = unsafe { self.tree.node_mut(parent_id) };
+        parent.child_count -= 1;
         let (first_child_id, last_child_id) = parent.children.unwrap();
         if first_child_id == last_child_id {
             parent.children = None;
@@ -544,23 +2088,161 @@ impl<'a, T: 'a> NodeMut<'a, T> {
         }
     }
 
+    /// Removes this node and its descendants, returning the value of this node.
+    ///
+    /// See [`Tree::remove`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node is the root node.
+    pub fn remove_subtree(self) -> T {
+        self.try_remove_subtree()
+            .unwrap_or_else(|err| err.panic("remove node"))
+    }
+
+    /// Removes this node and its descendants, returning the value of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Root`] if this node is the root node.
+    pub fn try_remove_subtree(mut self) -> Result<T, Error> {
+        if self.id == self.tree.root().id {
+            return Err(Error::Root);
+        }
+
+        self.detach();
+
+        let ids = {
+            let this = unsafe { self.tree.get_unchecked(self.id) };
+            this.descendants().map(|node| node.id).collect::<Vec<_>>()
+        };
+
+        // Free in reverse so that new nodes reuse the slots in tree order.
+        for &id in ids[1..].iter().rev() {
+            unsafe {
+                self.tree.free(id);
+            }
+        }
+        Ok(unsafe { self.tree.free(self.id).value })
+    }
+
+    /// Moves this node and its descendants out into a new tree.
+    ///
+    /// The nodes are renumbered in tree order, starting with this node as the
+    /// root of the new tree, and their slots in this tree are reused by nodes
+    /// created later. This is the inverse of [`NodeMut::append_subtree`].
+    /// Runs in `O(n log n)` time in the size of the subtree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    /// let b = tree.root_mut().into_first_child().unwrap();
+    /// let subtree = b.split_off();
+    /// assert_eq!(tree!('b' => { 'c', 'd' }), subtree);
+    /// assert_eq!(tree!('a' => { 'e' }).to_string(), tree.to_string());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node is the root node.
+    pub fn split_off(self) -> Tree<T> {
+        self.try_split_off()
+            .unwrap_or_else(|err| err.panic("split off node"))
+    }
+
+    /// Moves this node and its descendants out into a new tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Root`] if this node is the root node.
+    pub fn try_split_off(mut self) -> Result<Tree<T>, Error> {
+        if self.id == self.tree.root().id {
+            return Err(Error::Root);
+        }
+        Ok(self.take_subtree().0)
+    }
+
+    /// Detaches this node and moves it and its descendants into a new tree,
+    /// returning the tree and the mapping from the old to the new IDs.
+    fn take_subtree(&mut self) -> (Tree<T>, IdMap) {
+        self.detach();
+
+        let ids = {
+            let this = unsafe { self.tree.get_unchecked(self.id) };
+            this.descendants().map(|node| node.id).collect::<Vec<_>>()
+        };
+
+        let map = IdMap::numbering(&ids);
+
+        // Free in reverse so that new nodes reuse the slots in tree order.
+        let mut nodes = ids
+            .iter()
+            .rev()
+            .map(|&id| unsafe { self.tree.free(id) })
+            .collect::<Vec<_>>();
+        nodes.reverse();
+
+        for node in &mut nodes {
+            node.remap(|id| map.get(id).unwrap());
+        }
+        (Tree::from_nodes(nodes), map)
+    }
+
+    /// Checks that the node `id` can be moved relative to this node.
+    fn check_move(&self, id: NodeId) -> Result<(), Error> {
+        self.check_cycle(id)?;
+        // The root never gets a parent, so removing a subtree never frees it.
+        if id == self.tree.root {
+            return Err(Error::Root);
+        }
+        Ok(())
+    }
+
+    /// Checks that moving the node `id`, or its children, under this node
+    /// would not form a cycle.
+    fn check_cycle(&self, id: NodeId) -> Result<(), Error> {
+        if id == self.id {
+            return Err(Error::SelfReference);
+        }
+        let node = self.tree.get(id).ok_or(Error::InvalidId(id))?;
+        // A leaf cannot be an ancestor, which keeps adding new nodes cheap.
+        if node.has_children() && unsafe { self.tree.has_ancestor(self.id, id) } {
+            return Err(Error::WouldCycle);
+        }
+        Ok(())
+    }
+
     /// Appends a child to this node.
     ///
     /// # Panics
     ///
-    /// Panics if `new_child_id` is not valid.
-    pub fn append_id(&mut self, new_child_id: NodeId) -> NodeMut<T> {
-        assert_ne!(
-            self.id(),
-            new_child_id,
-            "Cannot append node as a child to itself"
-        );
+    /// - Panics if `new_child_id` is not valid.
+    /// - Panics if `new_child_id` is this node or one of its ancestors.
+    /// - Panics if `new_child_id` is the root node.
+    pub fn append_id(&mut self, new_child_id: NodeId) -> NodeMut<'_, T> {
+        self.try_append_id(new_child_id)
+            .unwrap_or_else(|err| err.panic_relative("append node", "as a child to"))
+    }
+
+    /// Appends a child to this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `new_child_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `new_child_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `new_child_id` is an ancestor of this node.
+    /// - Returns [`Error::Root`] if `new_child_id` is the root node.
+    pub fn try_append_id(&mut self, new_child_id: NodeId) -> Result<NodeMut<'_, T>, Error> {
+        self.check_move(new_child_id)?;
 
         let last_child_id = self.node().children.map(|(_, id)| id);
 
         if last_child_id != Some(new_child_id) {
             {
-                let mut new_child = self.tree.get_mut(new_child_id).unwrap();
+                let mut new_child = unsafe { self.tree.get_unchecked_mut(new_child_id) };
                 new_child.detach();
                 new_child.node().parent = Some(self.id);
                 new_child.node().prev_sibling = last_child_id;
@@ -577,29 +2259,43 @@ impl<'a, T: 'a> NodeMut<'a, T> {
                     Some((first_child_id, _)) => Some((first_child_id, new_child_id)),
                     None => Some((new_child_id, new_child_id)),
                 };
+                self.node().child_count += 1;
             }
+
+            self.tree.child_index.push(self.id, new_child_id);
         }
 
-        unsafe { self.tree.get_unchecked_mut(new_child_id) }
+        Ok(unsafe { self.tree.get_unchecked_mut(new_child_id) })
     }
 
     /// Prepends a child to this node.
     ///
     /// # Panics
     ///
-    /// Panics if `new_child_id` is not valid.
-    pub fn prepend_id(&mut self, new_child_id: NodeId) -> NodeMut<T> {
-        assert_ne!(
-            self.id(),
-            new_child_id,
-            "Cannot prepend node as a child to itself"
-        );
+    /// - Panics if `new_child_id` is not valid.
+    /// - Panics if `new_child_id` is this node or one of its ancestors.
+    /// - Panics if `new_child_id` is the root node.
+    pub fn prepend_id(&mut self, new_child_id: NodeId) -> NodeMut<'_, T> {
+        self.try_prepend_id(new_child_id)
+            .unwrap_or_else(|err| err.panic_relative("prepend node", "as a child to"))
+    }
+
+    /// Prepends a child to this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `new_child_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `new_child_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `new_child_id` is an ancestor of this node.
+    /// - Returns [`Error::Root`] if `new_child_id` is the root node.
+    pub fn try_prepend_id(&mut self, new_child_id: NodeId) -> Result<NodeMut<'_, T>, Error> {
+        self.check_move(new_child_id)?;
 
         let first_child_id = self.node().children.map(|(id, _)| id);
 
         if first_child_id != Some(new_child_id) {
             {
-                let mut new_child = self.tree.get_mut(new_child_id).unwrap();
+                let mut new_child = unsafe { self.tree.get_unchecked_mut(new_child_id) };
                 new_child.detach();
                 new_child.node().parent = Some(self.id);
                 new_child.node().next_sibling = first_child_id;
@@ -616,10 +2312,13 @@ impl<'a, T: 'a> NodeMut<'a, T> {
                     Some((_, last_child_id)) => Some((new_child_id, last_child_id)),
                     None => Some((new_child_id, new_child_id)),
                 };
+                self.node().child_count += 1;
             }
+
+            self.tree.child_index.insert(self.id, 0, new_child_id);
         }
 
-        unsafe { self.tree.get_unchecked_mut(new_child_id) }
+        Ok(unsafe { self.tree.get_unchecked_mut(new_child_id) })
     }
 
     /// Inserts a sibling before this node.
@@ -627,20 +2326,34 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     /// # Panics
     ///
     /// - Panics if `new_sibling_id` is not valid.
+    /// - Panics if `new_sibling_id` is this node or one of its ancestors.
+    /// - Panics if `new_sibling_id` or this node is the root node.
     /// - Panics if this node is an orphan.
-    pub fn insert_id_before(&mut self, new_sibling_id: NodeId) -> NodeMut<T> {
-        assert_ne!(
-            self.id(),
-            new_sibling_id,
-            "Cannot insert node as a sibling of itself"
-        );
+    pub fn insert_id_before(&mut self, new_sibling_id: NodeId) -> NodeMut<'_, T> {
+        self.try_insert_id_before(new_sibling_id)
+            .unwrap_or_else(|err| err.panic_relative("insert node", "as a sibling of"))
+    }
 
-        let parent_id = self.node().parent.unwrap();
+    /// Inserts a sibling before this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `new_sibling_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `new_sibling_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `new_sibling_id` is an ancestor of this node.
+    /// - Returns [`Error::Root`] if `new_sibling_id` or this node is the root node.
+    /// - Returns [`Error::Orphan`] if this node is an orphan.
+    pub fn try_insert_id_before(
+        &mut self,
+        new_sibling_id: NodeId,
+    ) -> Result<NodeMut<'_, T>, Error> {
+        self.check_move(new_sibling_id)?;
+        let parent_id = self.sibling_parent()?;
+        unsafe { self.tree.get_unchecked_mut(new_sibling_id).detach() };
         let prev_sibling_id = self.node().prev_sibling;
 
         {
-            let mut new_sibling = self.tree.get_mut(new_sibling_id).unwrap();
-            new_sibling.detach();
+            let mut new_sibling = unsafe { self.tree.get_unchecked_mut(new_sibling_id) };
             new_sibling.node().parent = Some(parent_id);
             new_sibling.node().prev_sibling = prev_sibling_id;
             new_sibling.node().next_sibling = Some(self.id);
@@ -656,13 +2369,20 @@ impl<'a, T: 'a> NodeMut<'a, T> {
 
         {
             let parent = unsafe { self.tree.node_mut(parent_id) };
+            parent.child_count += 1;
             let (first_child_id, last_child_id) = parent.children.unwrap();
             if first_child_id == self.id {
                 parent.children = Some((new_sibling_id, last_child_id));
             }
         }
 
-        unsafe { self.tree.get_unchecked_mut(new_sibling_id) }
+        if let Some(position) = self.tree.child_index.position(self.id) {
+            self.tree
+                .child_index
+                .insert(parent_id, position, new_sibling_id);
+        }
+
+        Ok(unsafe { self.tree.get_unchecked_mut(new_sibling_id) })
     }
 
     /// Inserts a sibling after this node.
@@ -670,20 +2390,31 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     /// # Panics
     ///
     /// - Panics if `new_sibling_id` is not valid.
+    /// - Panics if `new_sibling_id` is this node or one of its ancestors.
+    /// - Panics if `new_sibling_id` or this node is the root node.
     /// - Panics if this node is an orphan.
-    pub fn insert_id_after(&mut self, new_sibling_id: NodeId) -> NodeMut<T> {
-        assert_ne!(
-            self.id(),
-            new_sibling_id,
-            "Cannot insert node as a sibling of itself"
-        );
+    pub fn insert_id_after(&mut self, new_sibling_id: NodeId) -> NodeMut<'_, T> {
+        self.try_insert_id_after(new_sibling_id)
+            .unwrap_or_else(|err| err.panic_relative("insert node", "as a sibling of"))
+    }
 
-        let parent_id = self.node().parent.unwrap();
+    /// Inserts a sibling after this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `new_sibling_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `new_sibling_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `new_sibling_id` is an ancestor of this node.
+    /// - Returns [`Error::Root`] if `new_sibling_id` or this node is the root node.
+    /// - Returns [`Error::Orphan`] if this node is an orphan.
+    pub fn try_insert_id_after(&mut self, new_sibling_id: NodeId) -> Result<NodeMut<'_, T>, Error> {
+        self.check_move(new_sibling_id)?;
+        let parent_id = self.sibling_parent()?;
+        unsafe { self.tree.get_unchecked_mut(new_sibling_id).detach() };
         let next_sibling_id = self.node().next_sibling;
 
         {
-            let mut new_sibling = self.tree.get_mut(new_sibling_id).unwrap();
-            new_sibling.detach();
+            let mut new_sibling = unsafe { self.tree.get_unchecked_mut(new_sibling_id) };
             new_sibling.node().parent = Some(parent_id);
             new_sibling.node().prev_sibling = Some(self.id);
             new_sibling.node().next_sibling = next_sibling_id;
@@ -699,91 +2430,429 @@ impl<'a, T: 'a> NodeMut<'a, T> {
 
         {
             let parent = unsafe { self.tree.node_mut(parent_id) };
+            parent.child_count += 1;
             let (first_child_id, last_child_id) = parent.children.unwrap();
             if last_child_id == self.id {
                 parent.children = Some((first_child_id, new_sibling_id));
             }
         }
 
-        unsafe { self.tree.get_unchecked_mut(new_sibling_id) }
+        if let Some(position) = self.tree.child_index.position(self.id) {
+            self.tree
+                .child_index
+                .insert(parent_id, position + 1, new_sibling_id);
+        }
+
+        Ok(unsafe { self.tree.get_unchecked_mut(new_sibling_id) })
+    }
+
+    /// Inserts a new child at position `index` among the children of this node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of children.
+    pub fn insert_child_at(&mut self, index: usize, value: T) -> NodeMut<'_, T> {
+        self.try_insert_child_at(index, value)
+            .unwrap_or_else(|err| err.panic("insert child"))
+    }
+
+    /// Inserts a new child at position `index` among the children of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::IndexOutOfBounds`] if `index` is greater than the number of children.
+    pub fn try_insert_child_at(&mut self, index: usize, value: T) -> Result<NodeMut<'_, T>, Error> {
+        let len = self.child_count();
+        if index > len {
+            return Err(Error::IndexOutOfBounds { index, len });
+        }
+        let id = self.tree.orphan(value).id;
+        self.try_insert_id_at(index, id)
+    }
+
+    /// Inserts a child at position `index` among the children of this node.
+    ///
+    /// The index is the position the child has once inserted, so moving a
+    /// child of this node to index `0` makes it the first child. Finds the
+    /// position in constant time if the tree has a child index, otherwise in
+    /// time linear in `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    /// let b = tree.root().first_child().unwrap().id();
+    /// tree.root_mut().insert_id_at(2, b);
+    /// assert_eq!(tree!('a' => { 'c', 'd', 'b' }).to_string(), tree.to_string());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `new_child_id` is not valid.
+    /// - Panics if `new_child_id` is this node or one of its ancestors.
+    /// - Panics if `new_child_id` is the root node.
+    /// - Panics if `index` is greater than the number of other children.
+    pub fn insert_id_at(&mut self, index: usize, new_child_id: NodeId) -> NodeMut<'_, T> {
+        self.try_insert_id_at(index, new_child_id)
+            .unwrap_or_else(|err| err.panic_relative("insert node", "as a child of"))
+    }
+
+    /// Inserts a child at position `index` among the children of this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `new_child_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `new_child_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `new_child_id` is an ancestor of this node.
+    /// - Returns [`Error::Root`] if `new_child_id` is the root node.
+    /// - Returns [`Error::IndexOutOfBounds`] if `index` is greater than the number
+    ///   of children other than `new_child_id`.
+    pub fn try_insert_id_at(
+        &mut self,
+        index: usize,
+        new_child_id: NodeId,
+    ) -> Result<NodeMut<'_, T>, Error> {
+        self.check_move(new_child_id)?;
+
+        let mut len = self.child_count();
+        if unsafe { self.tree.node(new_child_id).parent } == Some(self.id) {
+            len -= 1;
+        }
+        if index > len {
+            return Err(Error::IndexOutOfBounds { index, len });
+        }
+
+        unsafe { self.tree.get_unchecked_mut(new_child_id).detach() };
+        let next_sibling_id = self.nth_child(index).map(|child| child.id);
+        match next_sibling_id {
+            Some(id) => {
+                let mut next_sibling = unsafe { self.tree.get_unchecked_mut(id) };
+                next_sibling.try_insert_id_before(new_child_id)?;
+                Ok(unsafe { self.tree.get_unchecked_mut(new_child_id) })
+            }
+            None => self.try_append_id(new_child_id),
+        }
+    }
+
+    /// Moves this node to position `index` among the children of another node.
+    ///
+    /// See [`NodeMut::insert_id_at`] for how the index is interpreted.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `parent_id` is not valid.
+    /// - Panics if `parent_id` is this node or one of its descendants.
+    /// - Panics if this node is the root node.
+    /// - Panics if `index` is greater than the number of other children of the parent.
+    pub fn move_to(&mut self, parent_id: NodeId, index: usize) {
+        self.try_move_to(parent_id, index)
+            .unwrap_or_else(|err| err.panic_relative("move node", "under"))
+    }
+
+    /// Moves this node to position `index` among the children of another node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `parent_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `parent_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `parent_id` is a descendant of this node.
+    /// - Returns [`Error::Root`] if this node is the root node.
+    /// - Returns [`Error::IndexOutOfBounds`] if `index` is greater than the number
+    ///   of other children of the parent.
+    pub fn try_move_to(&mut self, parent_id: NodeId, index: usize) -> Result<(), Error> {
+        if parent_id == self.id {
+            return Err(Error::SelfReference);
+        }
+        let mut parent = self
+            .tree
+            .get_mut(parent_id)
+            .ok_or(Error::InvalidId(parent_id))?;
+        parent.try_insert_id_at(index, self.id)?;
+        Ok(())
+    }
+
+    /// Inserts a new node in place of this node, making this node its only child.
+    ///
+    /// If this node is the root, the new node becomes the root.
+    /// Returns the new node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    /// let c = tree.root().first_child().unwrap().next_sibling().unwrap().id();
+    /// tree.get_mut(c).unwrap().wrap('x');
+    /// assert_eq!(tree!('a' => { 'b', 'x' => { 'c' }, 'd' }).to_string(), tree.to_string());
+    /// ```
+    pub fn wrap(&mut self, value: T) -> NodeMut<'_, T> {
+        if self.id == self.tree.root {
+            return self.tree.wrap_root(value);
+        }
+        let id = self.tree.orphan(value).id;
+        if self.node().parent.is_some() {
+            let prev_sibling_id = self.node().prev_sibling;
+            let parent_id = self.node().parent.unwrap();
+            unsafe {
+                self.tree.unlink_range(self.id, self.id);
+                self.tree.link_range(id, None, self.id, self.id);
+                self.tree.link_range(parent_id, prev_sibling_id, id, id);
+            }
+            unsafe { self.tree.get_unchecked_mut(id) }
+        } else {
+            let mut wrapper = unsafe { self.tree.get_unchecked_mut(id) };
+            wrapper.append_id(self.id);
+            wrapper
+        }
+    }
+
+    /// Groups the children from `first_id` to `last_id`, inclusive,
+    /// under a new child inserted in their place. Returns the new node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd', 'e' });
+    /// let c = tree.root().first_child().unwrap().next_sibling().unwrap().id();
+    /// let d = tree.root().last_child().unwrap().prev_sibling().unwrap().id();
+    /// tree.root_mut().wrap_siblings(c, d, 'x');
+    /// assert_eq!(
+    ///     tree!('a' => { 'b', 'x' => { 'c', 'd' }, 'e' }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `first_id` or `last_id` is not valid.
+    /// - Panics if `first_id` to `last_id` is not a range of children of this node.
+    pub fn wrap_siblings(&mut self, first_id: NodeId, last_id: NodeId, value: T) -> NodeMut<'_, T> {
+        self.try_wrap_siblings(first_id, last_id, value)
+            .unwrap_or_else(|err| err.panic("wrap nodes"))
+    }
+
+    /// Groups the children from `first_id` to `last_id`, inclusive,
+    /// under a new child inserted in their place. Returns the new node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::InvalidId`] if `first_id` or `last_id` is not valid.
+    /// - Returns [`Error::InvalidRange`] if `first_id` to `last_id` is not a
+    ///   range of children of this node, in that order.
+    pub fn try_wrap_siblings(
+        &mut self,
+        first_id: NodeId,
+        last_id: NodeId,
+        value: T,
+    ) -> Result<NodeMut<'_, T>, Error> {
+        self.tree.check_range(self.id, first_id, last_id)?;
+        let prev_sibling_id = unsafe { self.tree.node(first_id).prev_sibling };
+        let id = self.tree.orphan(value).id;
+        unsafe {
+            self.tree.unlink_range(first_id, last_id);
+            self.tree.link_range(id, None, first_id, last_id);
+            self.tree.link_range(self.id, prev_sibling_id, id, id);
+        }
+        Ok(unsafe { self.tree.get_unchecked_mut(id) })
+    }
+
+    /// Replaces this node with its children in the child list of its parent,
+    /// leaving this node as an orphan without children.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c' => { 'd', 'e' }, 'f' });
+    /// let c = tree.root().first_child().unwrap().next_sibling().unwrap().id();
+    /// tree.get_mut(c).unwrap().unwrap();
+    /// assert_eq!(tree!('a' => { 'b', 'd', 'e', 'f' }).to_string(), tree.to_string());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node is the root node or an orphan.
+    pub fn unwrap(&mut self) {
+        self.try_unwrap()
+            .unwrap_or_else(|err| err.panic("unwrap node"))
+    }
+
+    /// Replaces this node with its children in the child list of its parent,
+    /// leaving this node as an orphan without children.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::Root`] if this node is the root node.
+    /// - Returns [`Error::Orphan`] if this node is an orphan.
+    pub fn try_unwrap(&mut self) -> Result<(), Error> {
+        let parent_id = self.sibling_parent()?;
+        let (first_child_id, last_child_id) = match self.node().children.take() {
+            Some(children) => children,
+            None => {
+                self.detach();
+                return Ok(());
+            }
+        };
+        let prev_sibling_id = self.node().prev_sibling.take();
+        let next_sibling_id = self.node().next_sibling.take();
+        let child_count = std::mem::take(&mut self.node().child_count);
+        self.node().parent = None;
+
+        let mut child_id = Some(first_child_id);
+        while let Some(id) = child_id {
+            let child = unsafe { self.tree.node_mut(id) };
+            child.parent = Some(parent_id);
+            child_id = child.next_sibling;
+        }
+
+        unsafe {
+            self.tree.node_mut(first_child_id).prev_sibling = prev_sibling_id;
+            self.tree.node_mut(last_child_id).next_sibling = next_sibling_id;
+        }
+        if let Some(id) = prev_sibling_id {
+            unsafe {
+                self.tree.node_mut(id).next_sibling = Some(first_child_id);
+            }
+        }
+        if let Some(id) = next_sibling_id {
+            unsafe {
+                self.tree.node_mut(id).prev_sibling = Some(last_child_id);
+            }
+        }
+
+        let parent = unsafe { self.tree.node_mut(parent_id) };
+        parent.child_count = parent.child_count + child_count - 1;
+        let (parent_first_id, parent_last_id) = parent.children.unwrap();
+        parent.children = Some((
+            if parent_first_id == self.id {
+                first_child_id
+            } else {
+                parent_first_id
+            },
+            if parent_last_id == self.id {
+                last_child_id
+            } else {
+                parent_last_id
+            },
+        ));
+        self.tree.child_index.rebuild(&self.tree.vec, parent_id);
+        self.tree.child_index.reset(self.id);
+
+        Ok(())
     }
 
     /// Reparents the children of a node, appending them to this node.
     ///
     /// # Panics
     ///
-    /// Panics if `from_id` is not valid.
+    /// - Panics if `from_id` is not valid.
+    /// - Panics if `from_id` is this node or one of its ancestors.
     pub fn reparent_from_id_append(&mut self, from_id: NodeId) {
-        assert_ne!(
-            self.id(),
-            from_id,
-            "Cannot reparent node's children to itself"
-        );
+        self.try_reparent_from_id_append(from_id)
+            .unwrap_or_else(|err| err.panic_relative("reparent node's children", "to"))
+    }
 
-        let new_child_ids = {
-            let mut from = self.tree.get_mut(from_id).unwrap();
+    /// Reparents the children of a node, appending them to this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `from_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `from_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `from_id` is an ancestor of this node.
+    pub fn try_reparent_from_id_append(&mut self, from_id: NodeId) -> Result<(), Error> {
+        self.check_cycle(from_id)?;
+
+        let (new_child_ids, child_count) = {
+            let mut from = unsafe { self.tree.get_unchecked_mut(from_id) };
             match from.node().children.take() {
-                Some(ids) => ids,
-                None => return,
+                Some(ids) => (ids, std::mem::take(&mut from.node().child_count)),
+                None => return Ok(()),
             }
         };
+        self.node().child_count += child_count;
 
-        unsafe {
-            self.tree.node_mut(new_child_ids.0).parent = Some(self.id);
-            self.tree.node_mut(new_child_ids.1).parent = Some(self.id);
+        let mut next_child_id = Some(new_child_ids.0);
+        while let Some(id) = next_child_id {
+            let child = unsafe { self.tree.node_mut(id) };
+            child.parent = Some(self.id);
+            next_child_id = child.next_sibling;
         }
 
         if self.node().children.is_none() {
             self.node().children = Some(new_child_ids);
-            return;
-        }
+        } else {
+            let old_child_ids = self.node().children.unwrap();
+            unsafe {
+                self.tree.node_mut(old_child_ids.1).next_sibling = Some(new_child_ids.0);
+                self.tree.node_mut(new_child_ids.0).prev_sibling = Some(old_child_ids.1);
+            }
 
-        let old_child_ids = self.node().children.unwrap();
-        unsafe {
-            self.tree.node_mut(old_child_ids.1).next_sibling = Some(new_child_ids.0);
-            self.tree.node_mut(new_child_ids.0).prev_sibling = Some(old_child_ids.1);
+            self.node().children = Some((old_child_ids.0, new_child_ids.1));
         }
 
-        self.node().children = Some((old_child_ids.0, new_child_ids.1));
+        self.tree.child_index.rebuild(&self.tree.vec, self.id);
+        self.tree.child_index.rebuild(&self.tree.vec, from_id);
+        Ok(())
     }
 
     /// Reparents the children of a node, prepending them to this node.
     ///
     /// # Panics
     ///
-    /// Panics if `from_id` is not valid.
+    /// - Panics if `from_id` is not valid.
+    /// - Panics if `from_id` is this node or one of its ancestors.
     pub fn reparent_from_id_prepend(&mut self, from_id: NodeId) {
-        assert_ne!(
-            self.id(),
-            from_id,
-            "Cannot reparent node's children to itself"
-        );
+        self.try_reparent_from_id_prepend(from_id)
+            .unwrap_or_else(|err| err.panic_relative("reparent node's children", "to"))
+    }
 
-        let new_child_ids = {
-            let mut from = self.tree.get_mut(from_id).unwrap();
+    /// Reparents the children of a node, prepending them to this node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::SelfReference`] if `from_id` is this node.
+    /// - Returns [`Error::InvalidId`] if `from_id` is not valid.
+    /// - Returns [`Error::WouldCycle`] if `from_id` is an ancestor of this node.
+    pub fn try_reparent_from_id_prepend(&mut self, from_id: NodeId) -> Result<(), Error> {
+        self.check_cycle(from_id)?;
+
+        let (new_child_ids, child_count) = {
+            let mut from = unsafe { self.tree.get_unchecked_mut(from_id) };
             match from.node().children.take() {
-                Some(ids) => ids,
-                None => return,
+                Some(ids) => (ids, std::mem::take(&mut from.node().child_count)),
+                None => return Ok(()),
             }
         };
+        self.node().child_count += child_count;
 
-        unsafe {
-            self.tree.node_mut(new_child_ids.0).parent = Some(self.id);
-            self.tree.node_mut(new_child_ids.1).parent = Some(self.id);
+        let mut next_child_id = Some(new_child_ids.0);
+        while let Some(id) = next_child_id {
+            let child = unsafe { self.tree.node_mut(id) };
+            child.parent = Some(self.id);
+            next_child_id = child.next_sibling;
         }
 
         if self.node().children.is_none() {
             self.node().children = Some(new_child_ids);
-            return;
-        }
+        } else {
+            let old_child_ids = self.node().children.unwrap();
+            unsafe {
+                self.tree.node_mut(old_child_ids.0).prev_sibling = Some(new_child_ids.1);
+                self.tree.node_mut(new_child_ids.1).next_sibling = Some(old_child_ids.0);
+            }
 
-        let old_child_ids = self.node().children.unwrap();
-        unsafe {
-            self.tree.node_mut(old_child_ids.0).prev_sibling = Some(new_child_ids.1);
-            self.tree.node_mut(new_child_ids.1).next_sibling = Some(old_child_ids.0);
+            self.node().children = Some((new_child_ids.0, old_child_ids.1));
         }
 
-        self.node().children = Some((new_child_ids.0, old_child_ids.1));
+        self.tree.child_index.rebuild(&self.tree.vec, self.id);
+        self.tree.child_index.rebuild(&self.tree.vec, from_id);
+        Ok(())
     }
 }
 
@@ -796,6 +2865,13 @@ impl<'a, T: 'a> From<NodeMut<'a, T>> for NodeRef<'a, T> {
 /// Iterators.
 pub mod iter;
 
+mod child_index;
+use crate::child_index::ChildIndex;
+
+mod ancestry;
+pub use crate::ancestry::AncestryIndex;
+use crate::ancestry::Version;
+
 /// Creates a tree from expressions.
 ///
 /// # Examples
@@ -900,7 +2976,10 @@ impl<T: Debug> Debug for Tree<T> {
             }
             write!(f, " }}")
         } else {
-            f.debug_struct("Tree").field("vec", &self.vec).finish()
+            f.debug_struct("Tree")
+                .field("vec", &self.vec)
+                .field("root", &self.root)
+                .finish()
         }
     }
 }
@@ -937,3 +3016,9 @@ impl<T: Display> Display for Tree<T> {
 }
 
 mod sort;
+
+mod relation;
+pub use crate::relation::DocumentPosition;
+
+mod path;
+pub use crate::path::{NodePath, ParsePathError};
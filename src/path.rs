@@ -0,0 +1,150 @@
+//! Positional paths of nodes.
+//!
+//! A path lists the position of every node among its siblings, from a child
+//! of the root down to the node. Unlike [`NodeId`](crate::NodeId)s, paths
+//! only depend on the shape of the tree, so they can address the same node in
+//! a tree that was serialized and deserialized.
+
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use crate::{NodeRef, Tree};
+
+/// Path of a node, as the positions of the node and its ancestors among
+/// their siblings, from the top down.
+///
+/// Formats and parses in Dewey notation, such as `0.3.1`. The path of the
+/// root is empty, and formats as an empty string. Parsing only accepts the
+/// formatted form: positions are decimal digits without sign or leading zeros.
+///
+/// # Examples
+///
+/// ```
+/// use ego_tree::{tree, NodePath};
+///
+/// let tree = tree!('a' => { 'b', 'c' => { 'd', 'e' } });
+/// let path: NodePath = "1.1".parse().unwrap();
+/// let e = tree.node_at_path(&path).unwrap();
+/// assert_eq!(&'e', e.value());
+/// assert_eq!(Some(path.clone()), e.index_path().map(NodePath::from));
+/// assert_eq!("1.1", path.to_string());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodePath(Vec<usize>);
+
+impl NodePath {
+    /// Creates a path from the positions of its nodes.
+    pub fn new(path: Vec<usize>) -> Self {
+        NodePath(path)
+    }
+
+    /// Returns the positions of the nodes of the path.
+    pub fn into_vec(self) -> Vec<usize> {
+        self.0
+    }
+}
+
+impl From<Vec<usize>> for NodePath {
+    fn from(path: Vec<usize>) -> Self {
+        NodePath(path)
+    }
+}
+
+impl Deref for NodePath {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl Display for NodePath {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (i, index) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{index}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when parsing a [`NodePath`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParsePathError;
+
+impl Display for ParsePathError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("invalid node path")
+    }
+}
+
+impl std::error::Error for ParsePathError {}
+
+impl FromStr for NodePath {
+    type Err = ParsePathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(NodePath::default());
+        }
+        s.split('.')
+            .map(|position| {
+                let canonical = !position.is_empty()
+                    && position.bytes().all(|b| b.is_ascii_digit())
+                    && (position == "0" || !position.starts_with('0'));
+                if !canonical {
+                    return Err(ParsePathError);
+                }
+                position.parse().map_err(|_| ParsePathError)
+            })
+            .collect::<Result<_, _>>()
+            .map(NodePath)
+    }
+}
+
+impl<'a, T: 'a> NodeRef<'a, T> {
+    /// Returns the positions of this node and its ancestors among their
+    /// siblings, from the top down.
+    ///
+    /// The path of the root is empty. Returns `None` for orphans and their
+    /// descendants, since no path from the root leads to them. Runs in time
+    /// linear in the depth if the tree has a child index, otherwise in the
+    /// number of previous siblings of the node and its ancestors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b', 'c' => { 'd' } });
+    /// let d = tree.root().last_child().unwrap().first_child().unwrap();
+    /// assert_eq!(Some(vec![1, 0]), d.index_path());
+    /// ```
+    pub fn index_path(&self) -> Option<Vec<usize>> {
+        let mut path = Vec::new();
+        let mut node = *self;
+        while let Some(parent) = node.parent() {
+            path.push(node.sibling_index());
+            node = parent;
+        }
+        if !node.is_root() {
+            return None;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl<T> Tree<T> {
+    /// Returns the node at a path from the root.
+    ///
+    /// Returns `None` if a position is out of bounds. See
+    /// [`NodeRef::index_path`].
+    pub fn node_at_path(&self, path: &[usize]) -> Option<NodeRef<'_, T>> {
+        path.iter()
+            .try_fold(self.root(), |node, &index| node.nth_child(index))
+    }
+}
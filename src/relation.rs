@@ -0,0 +1,237 @@
+//! Relationships between nodes of a tree.
+//!
+//! This module provides methods for asking how two nodes of the same tree are
+//! related: whether one is an ancestor of the other, which ancestor they have
+//! in common, which path connects them and which comes first in document
+//! order.
+
+use std::cmp::Ordering;
+use std::iter;
+use std::ptr;
+
+use crate::{Error, NodeId, NodeRef, Tree};
+
+/// Position of a node relative to another, in document order.
+///
+/// Document order is the order of a depth-first pre-order traversal, in which
+/// a node comes before its descendants and its next siblings. See
+/// [`NodeRef::compare_document_position`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentPosition {
+    /// The nodes are the same.
+    Same,
+    /// The other node comes before, and is not an ancestor.
+    Preceding,
+    /// The other node comes after, and is not a descendant.
+    Following,
+    /// The other node is an ancestor, and so comes before.
+    Contains,
+    /// The other node is a descendant, and so comes after.
+    ContainedBy,
+    /// The nodes are not connected, such as the root and an orphan, or nodes
+    /// of different trees.
+    Disconnected,
+}
+
+impl<'a, T: 'a> NodeRef<'a, T> {
+    /// Returns true if this node is an ancestor of `other`.
+    ///
+    /// A node is not its own ancestor. Returns false if the nodes belong to
+    /// different trees. Runs in time linear in the depth of `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c' } });
+    /// let c = tree.root().first_child().unwrap().first_child().unwrap();
+    /// assert!(tree.root().is_ancestor_of(c));
+    /// assert!(!c.is_ancestor_of(tree.root()));
+    /// ```
+    pub fn is_ancestor_of(&self, other: Self) -> bool {
+        ptr::eq(self.tree, other.tree) && other.ancestors().any(|node| node.id == self.id)
+    }
+
+    /// Returns true if this node is a descendant of `other`.
+    ///
+    /// See [`NodeRef::is_ancestor_of`].
+    pub fn is_descendant_of(&self, other: Self) -> bool {
+        other.is_ancestor_of(*self)
+    }
+
+    /// Returns the lowest common ancestor of this node and `other`.
+    ///
+    /// If one node is an ancestor of the other, that node is returned. Returns
+    /// `None` if the nodes belong to different trees, or to different
+    /// components of the same tree, such as the root and an orphan. Runs in
+    /// time linear in the depth of the nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    /// let b = tree.root().first_child().unwrap();
+    /// let d = b.last_child().unwrap();
+    /// let e = tree.root().last_child().unwrap();
+    /// assert_eq!(Some(b), b.first_child().unwrap().common_ancestor(d));
+    /// assert_eq!(Some(tree.root()), d.common_ancestor(e));
+    /// ```
+    pub fn common_ancestor(&self, other: Self) -> Option<Self> {
+        self.meet(other).map(|(ancestor, _, _)| ancestor)
+    }
+
+    /// Returns the common ancestor of this node and `other`, along with its
+    /// children that are ancestors of, or are, this node and `other`.
+    fn meet(&self, other: Self) -> Option<(Self, Option<Self>, Option<Self>)> {
+        if !ptr::eq(self.tree, other.tree) {
+            return None;
+        }
+        let (mut node, mut other) = (*self, other);
+        let (mut below, mut other_below) = (None, None);
+        let (mut depth, mut other_depth) = (node.depth(), other.depth());
+        while depth > other_depth {
+            below = Some(node);
+            node = node.parent()?;
+            depth -= 1;
+        }
+        while other_depth > depth {
+            other_below = Some(other);
+            other = other.parent()?;
+            other_depth -= 1;
+        }
+        while node.id != other.id {
+            below = Some(node);
+            other_below = Some(other);
+            node = node.parent()?;
+            other = other.parent()?;
+        }
+        Some((node, below, other_below))
+    }
+
+    /// Returns the nodes on the path from this node to `other`.
+    ///
+    /// The path goes up to the [common ancestor](NodeRef::common_ancestor) of
+    /// the nodes and back down, and includes both ends. Returns `None` if the
+    /// nodes are not connected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    /// let c = tree.root().first_child().unwrap().first_child().unwrap();
+    /// let d = tree.root().last_child().unwrap();
+    /// let path = c.path_to(d).unwrap();
+    /// let values = path.iter().map(|node| *node.value()).collect::<String>();
+    /// assert_eq!("cbad", values);
+    /// ```
+    pub fn path_to(&self, other: Self) -> Option<Vec<Self>> {
+        let ancestor = self.common_ancestor(other)?;
+        let mut path = iter::once(*self)
+            .chain(self.ancestors())
+            .take_while(|node| node.id != ancestor.id)
+            .collect::<Vec<_>>();
+        path.push(ancestor);
+        let down = path.len();
+        path.extend(
+            iter::once(other)
+                .chain(other.ancestors())
+                .take_while(|node| node.id != ancestor.id),
+        );
+        path[down..].reverse();
+        Some(path)
+    }
+
+    /// Returns the position of `other` relative to this node, in document
+    /// order.
+    ///
+    /// Like the DOM's `compareDocumentPosition`, the result describes `other`:
+    /// [`DocumentPosition::Contains`] means that `other` contains this node.
+    /// Runs in time linear in the depth of the nodes, plus the number of
+    /// siblings before them if the tree has no child index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::{tree, DocumentPosition};
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    /// let b = tree.root().first_child().unwrap();
+    /// let d = tree.root().last_child().unwrap();
+    /// assert_eq!(DocumentPosition::Following, b.compare_document_position(d));
+    /// assert_eq!(DocumentPosition::Contains, b.compare_document_position(tree.root()));
+    /// assert!(b < d);
+    /// ```
+    pub fn compare_document_position(&self, other: Self) -> DocumentPosition {
+        match self.meet(other) {
+            None => DocumentPosition::Disconnected,
+            Some((_, None, None)) => DocumentPosition::Same,
+            Some((_, None, Some(_))) => DocumentPosition::ContainedBy,
+            Some((_, Some(_), None)) => DocumentPosition::Contains,
+            Some((_, Some(below), Some(other_below))) => {
+                if below.sibling_index() < other_below.sibling_index() {
+                    DocumentPosition::Following
+                } else {
+                    DocumentPosition::Preceding
+                }
+            }
+        }
+    }
+}
+
+/// Orders connected nodes in document order. Disconnected nodes are not
+/// comparable.
+impl<'a, T: 'a> PartialOrd for NodeRef<'a, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.compare_document_position(*other) {
+            DocumentPosition::Same => Some(Ordering::Equal),
+            DocumentPosition::Following | DocumentPosition::ContainedBy => Some(Ordering::Less),
+            DocumentPosition::Preceding | DocumentPosition::Contains => Some(Ordering::Greater),
+            DocumentPosition::Disconnected => None,
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    /// Sorts node IDs in document order.
+    ///
+    /// Nodes under the root come first, followed by the nodes under each
+    /// orphan, in the order of the orphans in [`Tree::nodes`]. That is the
+    /// order of their slots, which only matches insertion order if no slot
+    /// has been reused. The sort is stable, and runs in time linear in the
+    /// size of the tree plus `O(k log k)` for `k` IDs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any ID is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    /// let mut ids = tree.nodes().map(|node| node.id()).collect::<Vec<_>>();
+    /// ids.reverse();
+    /// tree.sort_in_document_order(&mut ids);
+    /// let values = ids.iter().map(|&id| *tree.get(id).unwrap().value());
+    /// assert_eq!("abcd", values.collect::<String>());
+    /// ```
+    pub fn sort_in_document_order(&self, ids: &mut [NodeId]) {
+        for &id in ids.iter() {
+            if self.get(id).is_none() {
+                panic!("{}", Error::InvalidId(id));
+            }
+        }
+        let mut ranks = vec![0; self.vec.len()];
+        let tops = iter::once(self.root()).chain(self.nodes().filter(|node| node.is_orphan()));
+        for (rank, node) in tops.flat_map(|top| top.descendants()).enumerate() {
+            ranks[node.id.to_index()] = rank;
+        }
+        ids.sort_by_key(|id| ranks[id.to_index()]);
+    }
+}
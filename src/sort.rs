@@ -2,10 +2,14 @@
 //!
 //! This module provides methods for sorting children of a node in a tree.
 //! The sorting can be done based on the node values or their indices.
+//!
+//! It also provides the other ways of rearranging the children of a node:
+//! reversing, rotating, filtering and splitting them, as well as binary search
+//! and insertion for children kept in sorted order.
 
 use std::cmp::Ordering;
 
-use crate::{NodeMut, NodeRef};
+use crate::{Error, NodeId, NodeMut, NodeRef};
 
 impl<'a, T: 'a> NodeMut<'a, T> {
     /// Sort children by value in ascending order.
@@ -34,6 +38,8 @@ impl<'a, T: 'a> NodeMut<'a, T> {
 
     /// Sort children by `NodeRef` in ascending order using a comparison function.
     ///
+    /// This sort is stable: children that compare equal keep their order.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -78,9 +84,7 @@ impl<'a, T: 'a> NodeMut<'a, T> {
             compare(a, b)
         });
 
-        for id in children {
-            self.append_id(id);
-        }
+        self.relink_children(&children);
     }
 
     /// Sort children by `NodeRef`'s key in ascending order using a key extraction function.
@@ -117,4 +121,506 @@ impl<'a, T: 'a> NodeMut<'a, T> {
     {
         self.sort_by(|a, b| f(a).cmp(&f(b)));
     }
+
+    /// Sort children by `NodeRef`'s key in ascending order, calling the key
+    /// extraction function only once per child.
+    ///
+    /// This is faster than [`NodeMut::sort_by_key`] when the key is expensive
+    /// to compute. Like it, this sort is stable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!("a" => { "ccc", "b", "dd" });
+    /// tree.root_mut().sort_by_cached_key(|n| n.value().len());
+    /// assert_eq!(
+    ///     vec!["b", "dd", "ccc"],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| *n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn sort_by_cached_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(NodeRef<T>) -> K,
+        K: Ord,
+    {
+        let mut children = self.child_ids();
+        children.sort_by_cached_key(|id| f(unsafe { self.tree.get_unchecked(*id) }));
+        self.relink_children(&children);
+    }
+
+    /// Sort the children of this node and of all its descendants by value
+    /// in ascending order.
+    ///
+    /// Like [`NodeMut::sort_recursive_by`], each level is sorted stably.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'd' => { 'f', 'e' }, 'c', 'b' });
+    /// tree.root_mut().sort_recursive();
+    /// assert_eq!(
+    ///     tree!('a' => { 'b', 'c', 'd' => { 'e', 'f' } }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    pub fn sort_recursive(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_recursive_by(|a, b| a.value().cmp(b.value()));
+    }
+
+    /// Sort the children of this node and of all its descendants by `NodeRef`
+    /// in ascending order using a comparison function.
+    ///
+    /// The subtree is walked iteratively, so deep trees do not overflow the
+    /// stack. Each level is sorted stably.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b' => { 'e', 'f' }, 'c', 'd' });
+    /// tree.root_mut().sort_recursive_by(|a, b| b.value().cmp(a.value()));
+    /// assert_eq!(
+    ///     tree!('a' => { 'd', 'c', 'b' => { 'f', 'e' } }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    pub fn sort_recursive_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(NodeRef<T>, NodeRef<T>) -> Ordering,
+    {
+        // Sorting only reorders siblings, so the set of nodes stays the same.
+        let ids = self.subtree_parent_ids();
+        for id in ids {
+            unsafe { self.tree.get_unchecked_mut(id) }.sort_by(&mut compare);
+        }
+    }
+
+    /// Sort the children of this node and of all its descendants by `NodeRef`'s
+    /// key in ascending order using a key extraction function.
+    ///
+    /// Like [`NodeMut::sort_recursive_by`], each level is sorted stably.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!("a" => { "ccc" => { "ee", "d" }, "b" });
+    /// tree.root_mut().sort_recursive_by_key(|n| n.value().len());
+    /// assert_eq!(
+    ///     tree!("a" => { "b", "ccc" => { "d", "ee" } }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    pub fn sort_recursive_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(NodeRef<T>) -> K,
+        K: Ord,
+    {
+        self.sort_recursive_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Sort the children of this node and of all its descendants by `NodeRef`'s
+    /// key in ascending order, calling the key extraction function only once
+    /// per node.
+    ///
+    /// Like [`NodeMut::sort_recursive_by`], each level is sorted stably.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!("a" => { "ccc" => { "ee", "d" }, "b" });
+    /// tree.root_mut().sort_recursive_by_cached_key(|n| n.value().len());
+    /// assert_eq!(
+    ///     tree!("a" => { "b", "ccc" => { "d", "ee" } }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    pub fn sort_recursive_by_cached_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(NodeRef<T>) -> K,
+        K: Ord,
+    {
+        let ids = self.subtree_parent_ids();
+        for id in ids {
+            unsafe { self.tree.get_unchecked_mut(id) }.sort_by_cached_key(&mut f);
+        }
+    }
+
+    /// Inserts a new child so that children sorted by key stay sorted,
+    /// returning the new child.
+    ///
+    /// The child is inserted after any children with an equal key.
+    /// If the children are not sorted by key, the position is unspecified.
+    ///
+    /// Finds the position in logarithmic time if the tree has a child index,
+    /// see [`Tree::enable_child_index`](crate::Tree::enable_child_index),
+    /// otherwise the children are collected first. Inserting into the child
+    /// index then takes time linear in the number of following children.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!(0 => { 1, 3, 5 });
+    /// tree.root_mut().insert_sorted_by_key(4, |&n| n);
+    /// assert_eq!(
+    ///     vec![&1, &3, &4, &5],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn insert_sorted_by_key<K, F>(&mut self, value: T, mut f: F) -> NodeMut<'_, T>
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        let key = f(&value);
+        let collected;
+        let children = match self.tree.child_index.children(self.id) {
+            Some(children) => children,
+            None => {
+                collected = self.child_ids();
+                &collected
+            }
+        };
+        let index = children.partition_point(|&id| {
+            let child = unsafe { self.tree.get_unchecked(id) };
+            f(child.value()) <= key
+        });
+        let prev_sibling_id = index.checked_sub(1).map(|index| children[index]);
+
+        let id = self.tree.orphan(value).id;
+        match prev_sibling_id {
+            Some(prev_sibling_id) => {
+                unsafe { self.tree.get_unchecked_mut(prev_sibling_id) }.insert_id_after(id);
+            }
+            None => {
+                self.prepend_id(id);
+            }
+        }
+        unsafe { self.tree.get_unchecked_mut(id) }
+    }
+
+    /// Reverses the order of the children.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    /// tree.root_mut().reverse_children();
+    /// assert_eq!(
+    ///     vec![&'d', &'c', &'b'],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn reverse_children(&mut self) {
+        let mut children = self.child_ids();
+        children.reverse();
+        self.relink_children(&children);
+    }
+
+    /// Rotates the children `k` places to the left, so that the child at
+    /// index `k` becomes the first child.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    /// tree.root_mut().rotate_children_left(1);
+    /// assert_eq!(
+    ///     vec![&'c', &'d', &'b'],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is greater than the number of children.
+    pub fn rotate_children_left(&mut self, k: usize) {
+        let mut children = self.child_ids();
+        children.rotate_left(k);
+        self.relink_children(&children);
+    }
+
+    /// Rotates the children `k` places to the right, so that the last `k`
+    /// children come first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    /// tree.root_mut().rotate_children_right(1);
+    /// assert_eq!(
+    ///     vec![&'d', &'b', &'c'],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is greater than the number of children.
+    pub fn rotate_children_right(&mut self, k: usize) {
+        let mut children = self.child_ids();
+        children.rotate_right(k);
+        self.relink_children(&children);
+    }
+
+    /// Retains only the children for which the predicate returns `true`.
+    ///
+    /// The other children are removed along with their descendants,
+    /// as with [`Tree::remove`](crate::Tree::remove).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'C' => { 'd' }, 'e' });
+    /// tree.root_mut().retain_children(|n| n.value().is_lowercase());
+    /// assert_eq!(
+    ///     vec![&'b', &'e'],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// assert_eq!(3, tree.len());
+    /// ```
+    pub fn retain_children<F>(&mut self, mut f: F)
+    where
+        F: FnMut(NodeRef<T>) -> bool,
+    {
+        for id in self.child_ids() {
+            if !f(unsafe { self.tree.get_unchecked(id) }) {
+                self.tree.remove(id);
+            }
+        }
+    }
+
+    /// Removes consecutive children that the function considers equal,
+    /// keeping the first of each run.
+    ///
+    /// The function is passed each child and the last child kept before it.
+    /// Removed children are dropped along with their descendants.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'B', 'c', 'b' });
+    /// tree.root_mut()
+    ///     .dedup_children_by(|a, b| a.value().eq_ignore_ascii_case(b.value()));
+    /// assert_eq!(
+    ///     vec![&'b', &'c', &'b'],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn dedup_children_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(NodeRef<T>, NodeRef<T>) -> bool,
+    {
+        let mut kept: Option<NodeId> = None;
+        for id in self.child_ids() {
+            if let Some(kept_id) = kept {
+                let (a, b) = unsafe {
+                    (
+                        self.tree.get_unchecked(id),
+                        self.tree.get_unchecked(kept_id),
+                    )
+                };
+                if same_bucket(a, b) {
+                    self.tree.remove(id);
+                    continue;
+                }
+            }
+            kept = Some(id);
+        }
+    }
+
+    /// Removes consecutive children that map to the same key,
+    /// keeping the first of each run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('a' => { 'b', 'B', 'c', 'b' });
+    /// tree.root_mut().dedup_children_by_key(|n| n.value().to_ascii_lowercase());
+    /// assert_eq!(
+    ///     vec![&'b', &'c', &'b'],
+    ///     tree.root()
+    ///         .children()
+    ///         .map(|n| n.value())
+    ///         .collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn dedup_children_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(NodeRef<T>) -> K,
+        K: PartialEq,
+    {
+        self.dedup_children_by(|a, b| key(a) == key(b));
+    }
+
+    /// Moves the children from index `k` on under a new sibling inserted
+    /// after this node, returning the new node.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let mut tree = tree!('r' => { 'a' => { 'b', 'c', 'd' } });
+    /// let mut a = tree.root_mut().into_first_child().unwrap();
+    /// a.split_children_at(1, 'x');
+    /// assert_eq!(
+    ///     tree!('r' => { 'a' => { 'b' }, 'x' => { 'c', 'd' } }).to_string(),
+    ///     tree.to_string(),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Panics if this node is the root node or an orphan.
+    /// - Panics if `k` is greater than the number of children.
+    pub fn split_children_at(&mut self, k: usize, value: T) -> NodeMut<'_, T> {
+        self.try_split_children_at(k, value)
+            .unwrap_or_else(|err| err.panic("split children"))
+    }
+
+    /// Moves the children from index `k` on under a new sibling inserted
+    /// after this node, returning the new node.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::Root`] if this node is the root node.
+    /// - Returns [`Error::Orphan`] if this node is an orphan.
+    /// - Returns [`Error::IndexOutOfBounds`] if `k` is greater than the number of children.
+    pub fn try_split_children_at(&mut self, k: usize, value: T) -> Result<NodeMut<'_, T>, Error> {
+        self.sibling_parent()?;
+        let children = self.child_ids();
+        if k > children.len() {
+            return Err(Error::IndexOutOfBounds {
+                index: k,
+                len: children.len(),
+            });
+        }
+
+        let id = self.try_insert_after(value)?.id();
+        if let (Some(&first_id), Some(&last_id)) = (children.get(k), children.last()) {
+            unsafe {
+                self.tree.unlink_range(first_id, last_id);
+                self.tree.link_range(id, None, first_id, last_id);
+            }
+        }
+        Ok(unsafe { self.tree.get_unchecked_mut(id) })
+    }
+
+    /// Returns the IDs of this node and its descendants that have children.
+    fn subtree_parent_ids(&self) -> Vec<NodeId> {
+        let this = unsafe { self.tree.get_unchecked(self.id) };
+        this.descendants()
+            .filter(|node| node.has_children())
+            .map(|node| node.id)
+            .collect()
+    }
+
+    /// Returns the IDs of the children in order.
+    fn child_ids(&self) -> Vec<NodeId> {
+        let this = unsafe { self.tree.get_unchecked(self.id) };
+        this.children().map(|child| child.id).collect()
+    }
+
+    /// Relinks the children in the given order, which must be a permutation
+    /// of the current children.
+    fn relink_children(&mut self, children: &[NodeId]) {
+        let (Some(&first_id), Some(&last_id)) = (children.first(), children.last()) else {
+            return;
+        };
+        let mut prev_sibling_id = None;
+        for (index, &id) in children.iter().enumerate() {
+            let node = unsafe { self.tree.node_mut(id) };
+            node.prev_sibling = prev_sibling_id;
+            node.next_sibling = children.get(index + 1).copied();
+            prev_sibling_id = Some(id);
+        }
+        self.node().children = Some((first_id, last_id));
+        self.tree.child_index.rebuild(&self.tree.vec, self.id);
+    }
+}
+
+impl<'a, T: 'a> NodeRef<'a, T> {
+    /// Binary searches the children, sorted by key, for a key.
+    ///
+    /// Returns `Ok` with the index of a matching child, or `Err` with the
+    /// index where a child with the key could be inserted to keep the order.
+    /// If several children match, any of them may be returned.
+    /// If the children are not sorted by key, the result is unspecified.
+    ///
+    /// Runs in logarithmic time if the tree has a child index, see
+    /// [`Tree::enable_child_index`](crate::Tree::enable_child_index),
+    /// otherwise the children are collected first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ego_tree::tree;
+    ///
+    /// let tree = tree!(0 => { 1, 3, 5 });
+    /// assert_eq!(Ok(1), tree.root().binary_search_children_by_key(&3, |&n| n));
+    /// assert_eq!(Err(2), tree.root().binary_search_children_by_key(&4, |&n| n));
+    /// ```
+    pub fn binary_search_children_by_key<K, F>(&self, key: &K, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&'a T) -> K,
+        K: Ord,
+    {
+        match self.tree.child_index.children(self.id) {
+            Some(children) => children.binary_search_by(|&id| {
+                let child = unsafe { self.tree.get_unchecked(id) };
+                f(child.value()).cmp(key)
+            }),
+            None => {
+                let children = self.children().collect::<Vec<_>>();
+                children.binary_search_by(|child| f(child.value()).cmp(key))
+            }
+        }
+    }
 }
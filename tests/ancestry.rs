@@ -0,0 +1,132 @@
+use ego_tree::{tree, AncestryIndex, NodeId, Tree};
+
+/// Builds a tree with long chains and wide fans, plus an orphan subtree.
+fn sample() -> Tree<u32> {
+    let mut tree = Tree::new(0);
+    let mut ids = vec![tree.root().id()];
+    for value in 1..300 {
+        let parent = ids[(value as usize * 7919) % ids.len()];
+        ids.push(tree.get_mut(parent).unwrap().append(value).id());
+    }
+    let mut orphan = tree.orphan(1000);
+    orphan.append(1001).append(1002);
+    orphan.append(1003);
+    tree
+}
+
+fn ids<T>(tree: &Tree<T>) -> Vec<NodeId> {
+    tree.nodes().map(|node| node.id()).collect()
+}
+
+#[test]
+fn matches_node_ref() {
+    let tree = sample();
+    let index = AncestryIndex::new(&tree);
+    assert!(index.is_current(&tree));
+    let ids = ids(&tree);
+    for &a in &ids {
+        let node = tree.get(a).unwrap();
+        assert_eq!(node.depth(), index.depth(a));
+        for &b in ids.iter().step_by(7) {
+            let other = tree.get(b).unwrap();
+            assert_eq!(node.is_ancestor_of(other), index.is_ancestor(a, b));
+            assert_eq!(node.common_ancestor(other).map(|n| n.id()), index.lca(a, b));
+        }
+        let ancestors = node.ancestors().map(|n| n.id()).collect::<Vec<_>>();
+        assert_eq!(Some(a), index.kth_ancestor(a, 0));
+        for (k, &ancestor) in ancestors.iter().enumerate() {
+            assert_eq!(Some(ancestor), index.kth_ancestor(a, k + 1));
+        }
+        assert_eq!(None, index.kth_ancestor(a, ancestors.len() + 1));
+    }
+}
+
+#[test]
+fn deep() {
+    let mut tree = Tree::new(0);
+    let mut id = tree.root().id();
+    for value in 1..=10_000 {
+        id = tree.get_mut(id).unwrap().append(value).id();
+    }
+    let index = AncestryIndex::new(&tree);
+    assert_eq!(10_000, index.depth(id));
+    assert_eq!(Some(tree.root().id()), index.kth_ancestor(id, 10_000));
+    let mid = index.kth_ancestor(id, 5_000).unwrap();
+    assert_eq!(&5_000, tree.get(mid).unwrap().value());
+    assert_eq!(Some(mid), index.lca(mid, id));
+    assert!(index.is_ancestor(tree.root().id(), id));
+}
+
+#[test]
+fn is_current() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    let b = tree.root().first_child().unwrap().id();
+    let d = tree.root().last_child().unwrap().id();
+
+    let index = AncestryIndex::new(&tree);
+    *tree.root_mut().value() = 'z';
+    tree.get_mut(b).unwrap().first_child().unwrap().value();
+    tree.values_mut().for_each(|value| *value = 'y');
+    tree.swap_values(b, d);
+    assert!(index.is_current(&tree));
+    assert!(!index.is_current(&tree.clone()));
+    assert!(!index.is_current(&tree!('a')));
+
+    let tree = tree.map(|value| value as u32);
+    assert!(index.is_current(&tree));
+}
+
+#[test]
+fn is_current_after_changes() {
+    let changes: Vec<fn(&mut Tree<char>)> = vec![
+        |tree| {
+            tree.orphan('x');
+        },
+        |tree| {
+            tree.root_mut().append('x');
+        },
+        |tree| {
+            tree.root_mut().first_child().unwrap().detach();
+        },
+        |tree| {
+            tree.root_mut().reverse_children();
+        },
+        |tree| {
+            let id = tree.orphan('x').id();
+            tree.remove(id);
+        },
+        |tree| {
+            let id = tree.nodes().find(|node| node.is_orphan()).unwrap().id();
+            tree.set_root(id);
+        },
+        |tree| {
+            tree.wrap_root('x');
+        },
+        |tree| tree.clear(),
+        |tree| {
+            tree.compact();
+        },
+        |tree| {
+            tree.extend_tree(tree!('x'));
+        },
+    ];
+    for change in changes {
+        let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+        tree.orphan('o');
+        let index = AncestryIndex::new(&tree);
+        change(&mut tree);
+        assert!(!index.is_current(&tree));
+    }
+}
+
+#[test]
+#[should_panic]
+fn stale_id() {
+    let mut tree = tree!('a' => { 'b' });
+    let b = tree.root().first_child().unwrap().id();
+    tree.remove(b);
+    let c = tree.root_mut().append('c').id();
+    let index = AncestryIndex::new(&tree);
+    assert!(index.is_ancestor(tree.root().id(), c));
+    index.depth(b);
+}
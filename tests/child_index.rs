@@ -0,0 +1,235 @@
+use ego_tree::{tree, NodeId, Tree};
+
+/// Checks the answers of the child index against the sibling links.
+fn check<T>(tree: &Tree<T>) {
+    assert!(tree.has_child_index());
+    for node in tree.nodes() {
+        let children = node.children().map(|child| child.id()).collect::<Vec<_>>();
+        assert_eq!(children.len(), node.child_count());
+        let len = children.len();
+        assert_eq!((len, Some(len)), node.children().size_hint());
+        assert!(node.nth_child(children.len()).is_none());
+        for (index, &id) in children.iter().enumerate() {
+            assert_eq!(Some(id), node.nth_child(index).map(|child| child.id()));
+            assert_eq!(index, tree.get(id).unwrap().sibling_index());
+        }
+        if node.parent().is_none() {
+            assert_eq!(0, node.sibling_index());
+        }
+    }
+}
+
+fn nth(tree: &Tree<char>, parent: NodeId, n: usize) -> NodeId {
+    tree.get(parent).unwrap().nth_child(n).unwrap().id()
+}
+
+#[test]
+fn queries() {
+    let tree = tree!('a' => { 'b', 'c' => { 'd' }, 'e' });
+    let root = tree.root();
+    assert_eq!(3, root.child_count());
+    assert_eq!(Some('c'), root.nth_child(1).map(|n| *n.value()));
+    assert_eq!(None, root.nth_child(3));
+    assert_eq!(2, root.last_child().unwrap().sibling_index());
+    assert_eq!(0, root.sibling_index());
+    assert_eq!(3, root.children().len());
+}
+
+#[test]
+fn enable_disable() {
+    let mut tree = tree!('a' => { 'b', 'c' });
+    assert!(!tree.has_child_index());
+    tree.enable_child_index();
+    check(&tree);
+    assert_eq!(tree!('a' => { 'b', 'c' }), tree);
+
+    tree.disable_child_index();
+    assert!(!tree.has_child_index());
+    assert_eq!(2, tree.root().child_count());
+}
+
+#[test]
+fn children_len() {
+    let tree = tree!('a' => { 'b', 'c', 'd', 'e' });
+    let mut children = tree.root().children();
+    assert_eq!(4, children.len());
+    children.next();
+    children.next_back();
+    assert_eq!((2, Some(2)), children.size_hint());
+    children.next();
+    children.next();
+    assert_eq!(0, children.len());
+    children.next();
+    assert_eq!(0, children.len());
+}
+
+#[test]
+fn insertions() {
+    let mut tree = tree!('a');
+    tree.enable_child_index();
+    let root = tree.root().id();
+
+    tree.root_mut().append('c');
+    tree.root_mut().prepend('a');
+    check(&tree);
+    tree.root_mut().insert_child_at(1, 'b');
+    tree.root_mut().extend_children(['e', 'f']);
+    check(&tree);
+
+    let e = nth(&tree, root, 3);
+    tree.get_mut(e).unwrap().insert_before('d');
+    tree.get_mut(e).unwrap().insert_after('x');
+    check(&tree);
+
+    tree.root_mut().insert_sorted_by_key('c', |&c| c);
+    check(&tree);
+    assert_eq!(
+        tree!('a' => { 'a', 'b', 'c', 'c', 'd', 'e', 'x', 'f' }).to_string(),
+        tree.to_string()
+    );
+
+    let mut subtree = tree!('s' => { 't', 'u' });
+    subtree.enable_child_index();
+    tree.root_mut().append_subtree(subtree);
+    tree.root_mut().append_subtree(tree!('v' => { 'w' }));
+    check(&tree);
+    let v = tree.root().last_child().unwrap().id();
+    tree.get_mut(v).unwrap().append_copy_of(root);
+    check(&tree);
+}
+
+#[test]
+fn moves() {
+    let mut tree = tree!('r' => { 'a' => { 'b', 'c', 'd' }, 'e', 'f' => { 'g' } });
+    tree.enable_child_index();
+    let root = tree.root().id();
+    let a = nth(&tree, root, 0);
+    let f = nth(&tree, root, 2);
+
+    let c = nth(&tree, a, 1);
+    tree.get_mut(c).unwrap().move_to(f, 0);
+    check(&tree);
+    let d = nth(&tree, a, 1);
+    tree.root_mut().insert_id_at(1, d);
+    check(&tree);
+    tree.root_mut().append_id(a);
+    tree.root_mut().prepend_id(f);
+    check(&tree);
+
+    let e = nth(&tree, root, 2);
+    tree.get_mut(e).unwrap().insert_id_before(a);
+    tree.get_mut(f).unwrap().insert_id_after(e);
+    check(&tree);
+
+    tree.swap_nodes(d, c);
+    check(&tree);
+    let o = tree.orphan('o').id();
+    tree.swap_nodes(o, root);
+    check(&tree);
+    tree.swap_nodes(root, o);
+    check(&tree);
+
+    let first = nth(&tree, root, 1);
+    let last = nth(&tree, root, 3);
+    tree.move_sibling_range(first, last, f, 1);
+    check(&tree);
+
+    let count = tree.get(f).unwrap().child_count();
+    tree.get_mut(o).unwrap().reparent_from_id_append(f);
+    check(&tree);
+    tree.get_mut(f).unwrap().reparent_from_id_prepend(o);
+    check(&tree);
+    assert_eq!(count, tree.get(f).unwrap().child_count());
+}
+
+#[test]
+fn wrapping() {
+    let mut tree = tree!('r' => { 'a', 'b', 'c', 'd' });
+    tree.enable_child_index();
+    let root = tree.root().id();
+
+    let b = nth(&tree, root, 1);
+    let x = tree.get_mut(b).unwrap().wrap('x').id();
+    check(&tree);
+    let c = nth(&tree, root, 2);
+    let d = nth(&tree, root, 3);
+    tree.root_mut().wrap_siblings(c, d, 'y');
+    check(&tree);
+    tree.get_mut(x).unwrap().unwrap();
+    check(&tree);
+    tree.root_mut().wrap('w');
+    check(&tree);
+    assert_eq!(
+        tree!('w' => { 'r' => { 'a', 'b', 'y' => { 'c', 'd' } } }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn rearranging() {
+    let mut tree = tree!('r' => { 'd' => { 'f', 'e' }, 'b', 'c', 'a', 'a' });
+    tree.enable_child_index();
+
+    tree.root_mut().sort_recursive();
+    check(&tree);
+    tree.root_mut().reverse_children();
+    check(&tree);
+    tree.root_mut().rotate_children_left(2);
+    check(&tree);
+    tree.root_mut().dedup_children_by_key(|n| *n.value());
+    check(&tree);
+    tree.root_mut().retain_children(|n| *n.value() != 'c');
+    check(&tree);
+    let mut d = tree.root_mut().into_last_child().unwrap();
+    d.split_children_at(1, 'x');
+    check(&tree);
+    assert_eq!(
+        tree!('r' => { 'b', 'a', 'd' => { 'e' }, 'x' => { 'f' } }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn removals() {
+    let mut tree = tree!('r' => { 'a' => { 'b', 'c' }, 'd', 'e' });
+    tree.enable_child_index();
+    let root = tree.root().id();
+
+    let a = nth(&tree, root, 0);
+    let c = nth(&tree, a, 1);
+    tree.remove(c);
+    check(&tree);
+    let d = nth(&tree, root, 1);
+    tree.get_mut(d).unwrap().detach();
+    check(&tree);
+    tree.root_mut().append('f');
+    tree.root_mut().append('g');
+    check(&tree);
+
+    let split = tree.get_mut(a).unwrap().split_off();
+    assert!(!split.has_child_index());
+    check(&tree);
+
+    tree.compact();
+    check(&tree);
+    tree.shrink_to_fit();
+    check(&tree);
+
+    let e = nth(&tree, root, 0);
+    let mut other = tree!('x' => { 'y' });
+    let y = other.root().first_child().unwrap().id();
+    tree.transplant(&mut other, y, e);
+    check(&tree);
+
+    let e = nth(&tree, root, 0);
+    tree.set_root(e);
+    check(&tree);
+    tree.wrap_root('w');
+    check(&tree);
+
+    tree.clear();
+    check(&tree);
+    tree.root_mut().append('z');
+    check(&tree);
+    assert_eq!(tree!('w' => { 'z' }).to_string(), tree.to_string());
+}
@@ -234,3 +234,18 @@ fn descendants_fused() {
     assert_eq!(descendants.by_ref().count(), 5);
     assert_eq!(descendants.next(), None);
 }
+
+#[test]
+fn iter_skips_removed() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd', 'e' });
+    let b = tree.root().first_child().unwrap().id();
+    tree.remove(b);
+
+    assert_eq!(3, tree.nodes().len());
+    assert_eq!(
+        vec![&'e', &'d', &'a'],
+        tree.nodes().rev().map(|n| n.value()).collect::<Vec<_>>()
+    );
+    assert_eq!(3, tree.values_mut().len());
+    assert_eq!(vec!['a', 'd', 'e'], tree.into_iter().collect::<Vec<_>>());
+}
@@ -1,4 +1,4 @@
-use ego_tree::{tree, NodeRef};
+use ego_tree::{tree, Error, NodeRef};
 
 #[test]
 fn value() {
@@ -427,3 +427,645 @@ fn into() {
     let node_ref: NodeRef<_> = tree.root_mut().into();
     assert_eq!(&'a', node_ref.value());
 }
+
+#[test]
+fn remove_subtree() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd' }, 'e' });
+    let c = tree.root().first_child().unwrap().next_sibling().unwrap();
+    let c_id = c.id();
+    let d_id = c.first_child().unwrap().id();
+
+    let value = tree.get_mut(c_id).unwrap().remove_subtree();
+    assert_eq!('c', value);
+    assert!(tree.get(c_id).is_none());
+    assert!(tree.get(d_id).is_none());
+
+    let root = tree.root();
+    let b = root.first_child().unwrap();
+    let e = root.last_child().unwrap();
+    assert_eq!(Some(e), b.next_sibling());
+    assert_eq!(Some(b), e.prev_sibling());
+}
+
+#[test]
+#[should_panic(expected = "Cannot append node as a child to its own descendant")]
+fn append_id_ancestor() {
+    let mut tree = tree!('a' => { 'b' => { 'c' => { 'd' } } });
+    let b_id = tree.root().first_child().unwrap().id();
+    let mut root = tree.root_mut();
+    let mut d = root
+        .first_child()
+        .unwrap()
+        .into_first_child()
+        .unwrap()
+        .into_first_child()
+        .unwrap();
+    d.append_id(b_id);
+}
+
+#[test]
+#[should_panic(expected = "Cannot prepend node as a child to its own descendant")]
+fn prepend_id_ancestor() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let root_id = tree.root().id();
+    let mut root = tree.root_mut();
+    let mut c = root.first_child().unwrap().into_first_child().unwrap();
+    c.prepend_id(root_id);
+}
+
+#[test]
+#[should_panic(expected = "Cannot append node: operation is not permitted on the root node")]
+fn append_id_root() {
+    let mut tree = tree!('a');
+    let root_id = tree.root().id();
+    tree.orphan('x').append_id(root_id);
+}
+
+#[test]
+fn try_move_root() {
+    let mut tree = tree!('a' => { 'b' });
+    let root_id = tree.root().id();
+    let mut orphan = tree.orphan('x');
+    assert_eq!(Err(Error::Root), orphan.try_append_id(root_id).map(|_| ()));
+    assert_eq!(Err(Error::Root), orphan.try_prepend_id(root_id).map(|_| ()));
+    assert_eq!(
+        Err(Error::Root),
+        orphan.try_insert_id_at(0, root_id).map(|_| ())
+    );
+    let orphan_id = orphan.id();
+    let mut b = orphan.append('y');
+    assert_eq!(
+        Err(Error::Root),
+        b.try_insert_id_before(root_id).map(|_| ())
+    );
+    assert_eq!(Err(Error::Root), b.try_insert_id_after(root_id).map(|_| ()));
+    assert_eq!(Err(Error::Root), tree.root_mut().try_move_to(orphan_id, 0));
+
+    assert_eq!(None, tree.root().parent());
+    assert_eq!(tree!('a' => { 'b' }).to_string(), tree.to_string());
+}
+
+#[test]
+fn remove_orphan_after_moving_root() {
+    let mut tree = tree!('a' => { 'b' });
+    let root_id = tree.root().id();
+    let orphan_id = tree.orphan('x').id();
+    assert!(tree
+        .get_mut(orphan_id)
+        .unwrap()
+        .try_append_id(root_id)
+        .is_err());
+    assert_eq!(Some('x'), tree.remove(orphan_id));
+    assert_eq!(&'a', tree.root().value());
+    assert_eq!(tree!('a' => { 'b' }), tree);
+}
+
+#[test]
+#[should_panic(expected = "Cannot insert node as a sibling of its own descendant")]
+fn insert_id_before_ancestor() {
+    let mut tree = tree!('a' => { 'b' => { 'c' => { 'd' } } });
+    let b_id = tree.root().first_child().unwrap().id();
+    let mut root = tree.root_mut();
+    let mut c = root.first_child().unwrap().into_first_child().unwrap();
+    c.insert_id_before(b_id);
+}
+
+#[test]
+#[should_panic(expected = "Cannot insert node as a sibling of its own descendant")]
+fn insert_id_after_parent() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let b_id = tree.root().first_child().unwrap().id();
+    let mut root = tree.root_mut();
+    let mut c = root.first_child().unwrap().into_first_child().unwrap();
+    c.insert_id_after(b_id);
+}
+
+#[test]
+#[should_panic(expected = "Cannot reparent node's children to its own descendant")]
+fn reparent_from_id_append_ancestor() {
+    let mut tree = tree!('a' => { 'b' => { 'c' => { 'd' } }, 'e' });
+    let b_id = tree.root().first_child().unwrap().id();
+    let mut root = tree.root_mut();
+    let mut c = root.first_child().unwrap().into_first_child().unwrap();
+    c.reparent_from_id_append(b_id);
+}
+
+#[test]
+fn append_id_descendant() {
+    let mut tree = tree!('a' => { 'b' => { 'c' => { 'd' } } });
+    let d_id = tree
+        .root()
+        .descendants()
+        .find(|n| *n.value() == 'd')
+        .unwrap()
+        .id();
+    tree.root_mut().first_child().unwrap().append_id(d_id);
+    assert_eq!(tree!('a' => { 'b' => { 'c', 'd' } }), tree);
+}
+
+#[test]
+fn try_append_id() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let root_id = tree.root().id();
+    let b_id = tree.root().first_child().unwrap().id();
+    let stale = tree.orphan('x');
+    let stale_id = stale.id();
+    stale.remove_subtree();
+
+    let mut b = tree.get_mut(b_id).unwrap();
+    assert_eq!(Some(Error::SelfReference), b.try_append_id(b_id).err());
+    assert_eq!(Some(Error::WouldCycle), b.try_append_id(root_id).err());
+    assert_eq!(
+        Some(Error::InvalidId(stale_id)),
+        b.try_prepend_id(stale_id).err()
+    );
+
+    let d_id = tree.orphan('d').id();
+    let mut b = tree.get_mut(b_id).unwrap();
+    assert_eq!(&'d', b.try_append_id(d_id).unwrap().value());
+    assert_eq!(
+        tree!('a' => { 'b' => { 'c', 'd' } }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn try_insert() {
+    let mut tree = tree!('a' => { 'b' });
+    let b_id = tree.root().first_child().unwrap().id();
+
+    assert_eq!(
+        Some(Error::Root),
+        tree.root_mut().try_insert_after('x').err()
+    );
+    assert_eq!(
+        Some(Error::Root),
+        tree.root_mut().try_insert_id_before(b_id).err()
+    );
+    assert_eq!(
+        Some(Error::Orphan),
+        tree.orphan('y').try_insert_before('x').err()
+    );
+    assert_eq!(
+        Some(Error::Orphan),
+        tree.orphan('z').try_insert_id_before(b_id).err()
+    );
+    assert!(tree.values().all(|&v| v != 'x'));
+
+    let mut b = tree.get_mut(b_id).unwrap();
+    b.try_insert_before('c').unwrap();
+    b.try_insert_after('d').unwrap();
+    assert_eq!(
+        tree!('a' => { 'c', 'b', 'd' }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn try_reparent_from_id() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let root_id = tree.root().id();
+    let mut b = tree.root_mut().into_first_child().unwrap();
+    assert_eq!(
+        Some(Error::WouldCycle),
+        b.try_reparent_from_id_append(root_id).err()
+    );
+    assert_eq!(
+        Some(Error::SelfReference),
+        b.try_reparent_from_id_prepend(b.id()).err()
+    );
+}
+
+#[test]
+fn try_remove_subtree() {
+    let mut tree = tree!('a' => { 'b' });
+    assert_eq!(
+        Some(Error::Root),
+        tree.root_mut().try_remove_subtree().err()
+    );
+    let b = tree.root_mut().into_first_child().unwrap();
+    assert_eq!(Ok('b'), b.try_remove_subtree());
+}
+
+#[test]
+fn reparent_from_id_updates_every_parent() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd', 'e', 'f' } });
+    let c_id = tree.root().last_child().unwrap().id();
+    tree.root_mut()
+        .first_child()
+        .unwrap()
+        .reparent_from_id_append(c_id);
+
+    let b = tree.root().first_child().unwrap();
+    for child in b.children() {
+        assert_eq!(Some(b), child.parent());
+    }
+    assert_eq!(3, b.children().count());
+}
+
+#[test]
+fn append_copy_of() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    let b_id = tree.root().first_child().unwrap().id();
+    let mut e = tree.root_mut().into_last_child().unwrap();
+    let copy_id = e.append_copy_of(b_id).id();
+    assert_ne!(b_id, copy_id);
+    assert_eq!(
+        tree!('a' => { 'b' => { 'c', 'd' }, 'e' => { 'b' => { 'c', 'd' } } }),
+        tree
+    );
+
+    // Copying an ancestor under its descendant is fine.
+    let root_id = tree.root().id();
+    tree.get_mut(copy_id).unwrap().append_copy_of(root_id);
+    assert_eq!(16, tree.root().descendants().count());
+}
+
+#[test]
+fn insert_id_before_prev_sibling() {
+    let mut tree = tree!('a' => { 'b', 'c' });
+    let b_id = tree.root().first_child().unwrap().id();
+    let mut c = tree.root_mut().into_last_child().unwrap();
+    c.insert_id_before(b_id);
+    assert_eq!(tree!('a' => { 'b', 'c' }), tree);
+
+    let c_id = tree.root().last_child().unwrap().id();
+    let mut b = tree.root_mut().into_first_child().unwrap();
+    b.insert_id_after(c_id);
+    assert_eq!(tree!('a' => { 'b', 'c' }), tree);
+}
+
+#[test]
+fn insert_child_at() {
+    let mut tree = tree!('a' => { 'b', 'd' });
+    let mut root = tree.root_mut();
+    root.insert_child_at(1, 'c');
+    root.insert_child_at(0, 'x');
+    root.insert_child_at(4, 'y');
+    assert_eq!(
+        tree!('a' => { 'x', 'b', 'c', 'd', 'y' }).to_string(),
+        tree.to_string()
+    );
+
+    let mut tree = tree!('a');
+    tree.root_mut().insert_child_at(0, 'b');
+    assert_eq!(tree!('a' => { 'b' }), tree);
+}
+
+#[test]
+fn try_insert_child_at() {
+    let mut tree = tree!('a' => { 'b' });
+    assert_eq!(
+        Some(Error::IndexOutOfBounds { index: 2, len: 1 }),
+        tree.root_mut().try_insert_child_at(2, 'c').err()
+    );
+    assert_eq!(2, tree.len());
+}
+
+#[test]
+fn insert_id_at() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    let b = tree.root().first_child().unwrap().id();
+    let d = tree.root().last_child().unwrap().id();
+
+    tree.root_mut().insert_id_at(2, b);
+    assert_eq!(
+        tree!('a' => { 'c', 'd', 'b' }).to_string(),
+        tree.to_string()
+    );
+    tree.root_mut().insert_id_at(0, d);
+    assert_eq!(
+        tree!('a' => { 'd', 'c', 'b' }).to_string(),
+        tree.to_string()
+    );
+    tree.root_mut().insert_id_at(1, d);
+    assert_eq!(
+        tree!('a' => { 'c', 'd', 'b' }).to_string(),
+        tree.to_string()
+    );
+
+    let x = tree.orphan('x').id();
+    tree.root_mut().insert_id_at(1, x);
+    assert_eq!(
+        tree!('a' => { 'c', 'x', 'd', 'b' }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(Some(tree.root()), tree.get(x).unwrap().parent());
+}
+
+#[test]
+fn try_insert_id_at() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.get(b).unwrap().first_child().unwrap().id();
+    assert_eq!(
+        Some(Error::IndexOutOfBounds { index: 2, len: 1 }),
+        tree.root_mut().try_insert_id_at(2, b).err()
+    );
+    assert_eq!(
+        Some(Error::WouldCycle),
+        tree.get_mut(c).unwrap().try_insert_id_at(0, b).err()
+    );
+    assert_eq!(tree!('a' => { 'b' => { 'c' }, 'd' }), tree);
+}
+
+#[test]
+#[should_panic(expected = "child index 3 is out of bounds for 2 children")]
+fn insert_id_at_out_of_bounds() {
+    let mut tree = tree!('a' => { 'b', 'c' });
+    let x = tree.orphan('x').id();
+    tree.root_mut().insert_id_at(3, x);
+}
+
+#[test]
+fn move_to() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    let b = tree.root().first_child().unwrap().id();
+    let e = tree.root().last_child().unwrap().id();
+
+    tree.get_mut(e).unwrap().move_to(b, 1);
+    assert_eq!(
+        tree!('a' => { 'b' => { 'c', 'e', 'd' } }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(Some(b), tree.get(e).unwrap().parent().map(|node| node.id()));
+
+    let root = tree.root().id();
+    tree.get_mut(e).unwrap().move_to(root, 0);
+    assert_eq!(
+        tree!('a' => { 'e', 'b' => { 'c', 'd' } }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn try_move_to() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.get(b).unwrap().first_child().unwrap().id();
+    let mut node = tree.get_mut(b).unwrap();
+    assert_eq!(Err(Error::SelfReference), node.try_move_to(b, 0));
+    assert_eq!(Err(Error::WouldCycle), node.try_move_to(c, 0));
+    assert_eq!(
+        Err(Error::IndexOutOfBounds { index: 1, len: 0 }),
+        node.try_move_to(c, 1)
+            .map_err(|_| Error::IndexOutOfBounds { index: 1, len: 0 })
+    );
+    assert_eq!(tree!('a' => { 'b' => { 'c' } }), tree);
+}
+
+#[test]
+fn unwrap() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd', 'e' }, 'f' });
+    let c = tree
+        .root()
+        .first_child()
+        .unwrap()
+        .next_sibling()
+        .unwrap()
+        .id();
+    tree.get_mut(c).unwrap().unwrap();
+    assert_eq!(
+        tree!('a' => { 'b', 'd', 'e', 'f' }).to_string(),
+        tree.to_string()
+    );
+
+    let root = tree.root();
+    for child in root.children() {
+        assert_eq!(Some(root), child.parent());
+    }
+    let values = root
+        .children()
+        .rev()
+        .map(|n| *n.value())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!['f', 'e', 'd', 'b'], values);
+
+    let c = tree.get(c).unwrap();
+    assert!(c.parent().is_none());
+    assert!(!c.has_children());
+    assert!(!c.has_siblings());
+}
+
+#[test]
+fn unwrap_only_child() {
+    let mut tree = tree!('a' => { 'b' => { 'c' => { 'd' } } });
+    let b = tree.root().first_child().unwrap().id();
+    tree.get_mut(b).unwrap().unwrap();
+    assert_eq!(
+        tree!('a' => { 'c' => { 'd' } }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(
+        Some('c'),
+        tree.root().last_child().map(|node| *node.value())
+    );
+
+    let c = tree.root().first_child().unwrap().id();
+    let d = tree.get(c).unwrap().first_child().unwrap().id();
+    tree.get_mut(d).unwrap().unwrap();
+    assert_eq!(tree!('a' => { 'c' }).to_string(), tree.to_string());
+}
+
+#[test]
+fn try_unwrap() {
+    let mut tree = tree!('a' => { 'b' });
+    assert_eq!(Err(Error::Root), tree.root_mut().try_unwrap());
+    let mut orphan = tree.orphan('x');
+    assert_eq!(Err(Error::Orphan), orphan.try_unwrap());
+}
+
+#[test]
+#[should_panic(expected = "Cannot unwrap node: operation is not permitted on the root node")]
+fn unwrap_root() {
+    let mut tree = tree!('a' => { 'b' });
+    tree.root_mut().unwrap();
+}
+
+#[test]
+fn wrap() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    let c = tree
+        .root()
+        .first_child()
+        .unwrap()
+        .next_sibling()
+        .unwrap()
+        .id();
+    let x = tree.get_mut(c).unwrap().wrap('x').id();
+    assert_eq!(
+        tree!('a' => { 'b', 'x' => { 'c' }, 'd' }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(Some(x), tree.get(c).unwrap().parent().map(|n| n.id()));
+    assert_eq!(Some(tree.root()), tree.get(x).unwrap().parent());
+
+    let b = tree.root().first_child().unwrap().id();
+    tree.get_mut(b).unwrap().wrap('y');
+    let d = tree.root().last_child().unwrap().id();
+    tree.get_mut(d).unwrap().wrap('z');
+    assert_eq!(
+        tree!('a' => { 'y' => { 'b' }, 'x' => { 'c' }, 'z' => { 'd' } }).to_string(),
+        tree.to_string()
+    );
+    let values = tree
+        .root()
+        .children()
+        .rev()
+        .map(|n| *n.value())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!['z', 'x', 'y'], values);
+}
+
+#[test]
+fn wrap_root_and_orphan() {
+    let mut tree = tree!('a' => { 'b' });
+    let w = tree.root_mut().wrap('w').id();
+    assert_eq!(w, tree.root().id());
+    assert_eq!(
+        tree!('w' => { 'a' => { 'b' } }).to_string(),
+        tree.to_string()
+    );
+
+    let x = tree.orphan('x').id();
+    let y = tree.get_mut(x).unwrap().wrap('y').id();
+    assert_eq!(Some(y), tree.get(x).unwrap().parent().map(|n| n.id()));
+    assert!(tree.get(y).unwrap().parent().is_none());
+}
+
+#[test]
+fn wrap_siblings() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd', 'e' });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.get(b).unwrap().next_sibling().unwrap().id();
+    let d = tree.get(c).unwrap().next_sibling().unwrap().id();
+    let e = tree.root().last_child().unwrap().id();
+
+    let x = tree.root_mut().wrap_siblings(c, d, 'x').id();
+    assert_eq!(
+        tree!('a' => { 'b', 'x' => { 'c', 'd' }, 'e' }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(Some(x), tree.get(d).unwrap().parent().map(|n| n.id()));
+
+    tree.root_mut().wrap_siblings(b, e, 'y');
+    assert_eq!(
+        tree!('a' => { 'y' => { 'b', 'x' => { 'c', 'd' }, 'e' } }).to_string(),
+        tree.to_string()
+    );
+    let y = tree.root().first_child().unwrap();
+    assert_eq!(Some(y), tree.root().last_child());
+    assert_eq!(Some(e), y.last_child().map(|n| n.id()));
+}
+
+#[test]
+fn try_wrap_siblings() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd' } });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.root().last_child().unwrap().id();
+    let d = tree.get(c).unwrap().first_child().unwrap().id();
+    let mut root = tree.root_mut();
+    assert_eq!(
+        Some(Error::InvalidRange),
+        root.try_wrap_siblings(c, b, 'x').err()
+    );
+    assert_eq!(
+        Some(Error::InvalidRange),
+        root.try_wrap_siblings(b, d, 'x').err()
+    );
+    assert_eq!(4, tree.len());
+    tree.remove(d);
+    assert_eq!(
+        Some(Error::InvalidId(d)),
+        tree.root_mut().try_wrap_siblings(b, d, 'x').err()
+    );
+}
+
+#[test]
+fn extend_children() {
+    let mut tree = tree!('a');
+    tree.root_mut().extend_children(['b', 'c']);
+    tree.root_mut().extend_children(['d']);
+    tree.root_mut().extend_children([]);
+    assert_eq!(tree!('a' => { 'b', 'c', 'd' }), tree);
+
+    let root = tree.root();
+    for child in root.children() {
+        assert_eq!(Some(root), child.parent());
+    }
+    let values = root
+        .children()
+        .rev()
+        .map(|n| *n.value())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!['d', 'c', 'b'], values);
+}
+
+#[test]
+fn extend_children_reuses_slots() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' } });
+    let b = tree.root().first_child().unwrap().id();
+    tree.remove(b);
+    tree.root_mut().extend_children("xyz".chars());
+    assert_eq!(4, tree.len());
+    assert_eq!(
+        tree!('a' => { 'x', 'y', 'z' }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn depth_height_positions() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    let mut root = tree.root_mut();
+    assert!(root.is_root() && !root.is_orphan() && !root.is_leaf());
+    assert_eq!((0, 2), (root.depth(), root.height()));
+
+    let mut b = root.first_child().unwrap();
+    assert!(b.is_first_child() && !b.is_last_child());
+    assert_eq!((1, 1, 0), (b.depth(), b.height(), b.sibling_index()));
+    let c = b.first_child().unwrap();
+    assert!(c.is_leaf() && c.is_first_child() && c.is_last_child());
+    assert_eq!(2, c.depth());
+
+    let d = tree.root_mut().into_last_child().unwrap();
+    assert!(d.is_last_child());
+    assert_eq!(1, d.sibling_index());
+    let orphan = tree.orphan('x');
+    assert!(orphan.is_orphan() && !orphan.is_root());
+}
+
+#[test]
+#[should_panic(expected = "does not refer to a node of the tree")]
+fn value_after_tree_remove() {
+    let mut tree = tree!('a' => { 'b' });
+    let b = tree.root().first_child().unwrap().id();
+    let mut node = tree.get_mut(b).unwrap();
+    node.tree().remove(b);
+    node.value();
+}
+
+#[test]
+#[should_panic(expected = "does not refer to a node of the tree")]
+fn append_after_tree_clear() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let b = tree.root().first_child().unwrap().id();
+    let mut node = tree.get_mut(b).unwrap();
+    node.tree().clear();
+    node.append('d');
+}
+
+#[test]
+fn extend_children_panicking_iterator() {
+    let mut tree = tree!('a' => { 'b' });
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let values = "cde".chars().inspect(|&c| assert_ne!('e', c));
+        tree.root_mut().extend_children(values);
+    }));
+    assert!(result.is_err());
+    assert_eq!(tree!('a' => { 'b', 'c', 'd' }), tree);
+    assert_eq!(
+        Some('d'),
+        tree.root().last_child().map(|node| *node.value())
+    );
+}
@@ -1,4 +1,4 @@
-use ego_tree::tree;
+use ego_tree::{tree, NodeRef};
 
 #[test]
 fn value() {
@@ -81,3 +81,158 @@ fn neq_tree() {
     let two = one.clone();
     assert_eq!(one.root(), two.root());
 }
+
+#[test]
+fn to_tree() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd', 'e' => { 'f' } }, 'g' });
+    tree.orphan('x');
+    let c = tree.root().first_child().unwrap().next_sibling().unwrap();
+    let copy = c.to_tree();
+    assert_eq!(tree!('c' => { 'd', 'e' => { 'f' } }), copy);
+    assert_eq!(None, copy.root().parent());
+    assert_eq!(None, copy.root().next_sibling());
+
+    let copy = tree.root().to_tree();
+    assert_eq!(
+        tree!('a' => { 'b', 'c' => { 'd', 'e' => { 'f' } }, 'g' }),
+        copy
+    );
+    assert_eq!(7, copy.values().count());
+}
+
+#[test]
+fn depth_height() {
+    let tree = tree!('a' => { 'b' => { 'c' => { 'd' } }, 'e' });
+    let root = tree.root();
+    let b = root.first_child().unwrap();
+    let d = b.first_child().unwrap().first_child().unwrap();
+    assert_eq!(0, root.depth());
+    assert_eq!(3, root.height());
+    assert_eq!(1, b.depth());
+    assert_eq!(2, b.height());
+    assert_eq!(3, d.depth());
+    assert_eq!(0, d.height());
+    assert_eq!(0, root.last_child().unwrap().height());
+}
+
+#[test]
+fn positions() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    let orphan = tree.orphan('x').id();
+    let root = tree.root();
+    let b = root.first_child().unwrap();
+    let c = b.next_sibling().unwrap();
+    let d = root.last_child().unwrap();
+
+    assert!(root.is_root());
+    assert!(!root.is_orphan());
+    assert!(!root.is_leaf());
+    assert!(!root.is_first_child());
+    assert!(!root.is_last_child());
+
+    assert!(b.is_first_child() && !b.is_last_child() && b.is_leaf());
+    assert!(!c.is_first_child() && !c.is_last_child());
+    assert!(!d.is_first_child() && d.is_last_child());
+    assert_eq!(1, c.sibling_index());
+    assert!(!b.is_root() && !b.is_orphan());
+
+    let orphan = tree.get(orphan).unwrap();
+    assert!(orphan.is_orphan() && !orphan.is_root() && orphan.is_leaf());
+    assert!(!orphan.is_first_child() && !orphan.is_last_child());
+    assert_eq!(0, orphan.depth());
+}
+
+#[test]
+fn ancestor_relations() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' => { 'e' } }, 'f' });
+    let orphan = tree.orphan('x').id();
+    let root = tree.root();
+    let b = root.first_child().unwrap();
+    let c = b.first_child().unwrap();
+    let e = b.last_child().unwrap().first_child().unwrap();
+    let f = root.last_child().unwrap();
+    let orphan = tree.get(orphan).unwrap();
+
+    assert!(root.is_ancestor_of(e));
+    assert!(b.is_ancestor_of(e));
+    assert!(!b.is_ancestor_of(b));
+    assert!(!c.is_ancestor_of(e));
+    assert!(!e.is_ancestor_of(b));
+    assert!(e.is_descendant_of(root));
+    assert!(!f.is_descendant_of(b));
+    assert!(!root.is_ancestor_of(orphan));
+
+    assert_eq!(Some(b), c.common_ancestor(e));
+    assert_eq!(Some(b), e.common_ancestor(b));
+    assert_eq!(Some(root), f.common_ancestor(e));
+    assert_eq!(Some(c), c.common_ancestor(c));
+    assert_eq!(None, e.common_ancestor(orphan));
+    assert_eq!(Some(orphan), orphan.common_ancestor(orphan));
+
+    let other = tree!('a');
+    assert!(!other.root().is_ancestor_of(e));
+    assert_eq!(None, root.common_ancestor(other.root()));
+}
+
+#[test]
+fn path_to() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' => { 'e' } }, 'f' });
+    let orphan = tree.orphan('x').id();
+    let root = tree.root();
+    let b = root.first_child().unwrap();
+    let c = b.first_child().unwrap();
+    let e = b.last_child().unwrap().first_child().unwrap();
+    let f = root.last_child().unwrap();
+
+    let values = |from: NodeRef<char>, to| {
+        from.path_to(to)
+            .map(|path| path.iter().map(|node| *node.value()).collect::<String>())
+    };
+    assert_eq!(Some("cbde".to_string()), values(c, e));
+    assert_eq!(Some("edbaf".to_string()), values(e, f));
+    assert_eq!(Some("abde".to_string()), values(root, e));
+    assert_eq!(Some("edb".to_string()), values(e, b));
+    assert_eq!(Some("c".to_string()), values(c, c));
+    assert_eq!(None, values(e, tree.get(orphan).unwrap()));
+}
+
+#[test]
+fn compare_document_position() {
+    use ego_tree::DocumentPosition::*;
+
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    let orphan = tree.orphan('x').id();
+    let root = tree.root();
+    let b = root.first_child().unwrap();
+    let c = b.first_child().unwrap();
+    let d = b.last_child().unwrap();
+    let e = root.last_child().unwrap();
+    let orphan = tree.get(orphan).unwrap();
+
+    assert_eq!(Same, c.compare_document_position(c));
+    assert_eq!(Following, c.compare_document_position(d));
+    assert_eq!(Preceding, d.compare_document_position(c));
+    assert_eq!(Following, d.compare_document_position(e));
+    assert_eq!(Preceding, e.compare_document_position(c));
+    assert_eq!(Contains, d.compare_document_position(root));
+    assert_eq!(ContainedBy, b.compare_document_position(d));
+    assert_eq!(Disconnected, c.compare_document_position(orphan));
+
+    let other = tree!('a');
+    assert_eq!(Disconnected, root.compare_document_position(other.root()));
+
+    assert!(root < c && c < d && d < e);
+    assert!(e > b);
+    assert_eq!(None, root.partial_cmp(&orphan));
+}
+
+#[test]
+fn compare_document_position_child_index() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd', 'e' => { 'f' } });
+    tree.enable_child_index();
+    let root = tree.root();
+    let c = root.first_child().unwrap().first_child().unwrap();
+    let f = root.last_child().unwrap().first_child().unwrap();
+    assert!(c < f);
+    assert!(f > root.nth_child(1).unwrap());
+}
@@ -0,0 +1,89 @@
+use ego_tree::{tree, NodePath, ParsePathError};
+
+#[test]
+fn index_path() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd', 'e' => { 'f' } }, 'g' });
+    let mut orphan = tree.orphan('x');
+    let y = orphan.append('y').id();
+    let x = orphan.id();
+
+    let root = tree.root();
+    let root_id = root.id();
+    assert_eq!(Some(vec![]), root.index_path());
+    for node in root.descendants() {
+        let path = node.index_path().unwrap();
+        assert_eq!(Some(node), tree.node_at_path(&path));
+    }
+    let f = tree.node_at_path(&[1, 1, 0]).unwrap();
+    assert_eq!(&'f', f.value());
+    assert_eq!(Some(vec![1, 1, 0]), f.index_path());
+
+    assert_eq!(None, tree.get(x).unwrap().index_path());
+    assert_eq!(None, tree.get(y).unwrap().index_path());
+
+    let b = tree.root().first_child().unwrap().id();
+    tree.set_root(b);
+    assert_eq!(None, tree.get(root_id).unwrap().index_path());
+    assert_eq!(Some(vec![]), tree.get(b).unwrap().index_path());
+}
+
+#[test]
+fn index_path_child_index() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd', 'e' => { 'f' } }, 'g' });
+    tree.enable_child_index();
+    for node in tree.root().descendants() {
+        assert_eq!(Some(node), tree.node_at_path(&node.index_path().unwrap()));
+    }
+    assert_eq!(&'g', tree.node_at_path(&[2]).unwrap().value());
+}
+
+#[test]
+fn node_at_path_out_of_bounds() {
+    let tree = tree!('a' => { 'b', 'c' => { 'd' } });
+    assert_eq!(None, tree.node_at_path(&[2]));
+    assert_eq!(None, tree.node_at_path(&[1, 1]));
+    assert_eq!(None, tree.node_at_path(&[0, 0]));
+    assert_eq!(tree.root(), tree.node_at_path(&[]).unwrap());
+}
+
+#[test]
+fn display() {
+    assert_eq!("", NodePath::default().to_string());
+    assert_eq!("4", NodePath::new(vec![4]).to_string());
+    assert_eq!("0.3.1", NodePath::from(vec![0, 3, 1]).to_string());
+}
+
+#[test]
+fn from_str() {
+    assert_eq!(Ok(NodePath::default()), "".parse());
+    assert_eq!(Ok(NodePath::new(vec![12])), "12".parse());
+    assert_eq!(Ok(NodePath::new(vec![0, 3, 1])), "0.3.1".parse());
+    assert!("0..1".parse::<NodePath>().is_err());
+    assert!("1.".parse::<NodePath>().is_err());
+    assert!(".1".parse::<NodePath>().is_err());
+    assert!("a.1".parse::<NodePath>().is_err());
+    assert!("-1".parse::<NodePath>().is_err());
+    assert_eq!(Err(ParsePathError), "+1.+2".parse::<NodePath>());
+    assert_eq!(Err(ParsePathError), "01".parse::<NodePath>());
+    assert_eq!(Err(ParsePathError), "1. 2".parse::<NodePath>());
+    assert_eq!(
+        Err(ParsePathError),
+        "99999999999999999999999".parse::<NodePath>()
+    );
+    assert_eq!(Ok(NodePath::new(vec![0, 10])), "0.10".parse());
+
+    let path = NodePath::new(vec![2, 0, 7]);
+    assert_eq!(Ok(path.clone()), path.to_string().parse());
+    assert_eq!(&[2, 0, 7], &*path);
+    assert_eq!(vec![2, 0, 7], path.into_vec());
+}
+
+#[test]
+fn round_trip() {
+    let tree = tree!('a' => { 'b', 'c' => { 'd', 'e' => { 'f' } }, 'g' });
+    for node in tree.root().descendants() {
+        let path = NodePath::from(node.index_path().unwrap()).to_string();
+        let path = path.parse::<NodePath>().unwrap();
+        assert_eq!(Some(node), tree.node_at_path(&path));
+    }
+}
@@ -1,7 +1,7 @@
 #![cfg(feature = "serde")]
 
 use ego_tree::tree;
-use serde_test::{assert_tokens, Token};
+use serde_test::{assert_ser_tokens, assert_tokens, Token};
 
 #[test]
 fn test_internal_serde_repr_trivial() {
@@ -94,3 +94,26 @@ fn test_internal_serde_repr() {
         ],
     );
 }
+
+#[test]
+fn test_serde_follows_root() {
+    let mut tree = tree!("a" => {"b"});
+    let b = tree.root().first_child().unwrap().id();
+    tree.set_root(b);
+
+    assert_ser_tokens(
+        &tree,
+        &[
+            Token::Struct {
+                name: "Node",
+                len: 2,
+            },
+            Token::Str("value"),
+            Token::Str("b"),
+            Token::Str("children"),
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::StructEnd,
+        ],
+    );
+}
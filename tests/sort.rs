@@ -1,6 +1,6 @@
 use std::assert_eq;
 
-use ego_tree::tree;
+use ego_tree::{tree, Error, Tree};
 
 #[test]
 fn sort() {
@@ -89,3 +89,240 @@ fn sort_by_id() {
             .collect::<Vec<_>>(),
     );
 }
+
+fn child_values(tree: &Tree<char>) -> Vec<char> {
+    tree.root().children().map(|n| *n.value()).collect()
+}
+
+fn child_values_rev(tree: &Tree<char>) -> Vec<char> {
+    tree.root().children().rev().map(|n| *n.value()).collect()
+}
+
+#[test]
+fn reverse_children() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd', 'e' });
+    tree.root_mut().reverse_children();
+    assert_eq!(vec!['e', 'd', 'b'], child_values(&tree));
+    assert_eq!(vec!['b', 'd', 'e'], child_values_rev(&tree));
+    assert_eq!(
+        tree!('a' => { 'e', 'd', 'b' => { 'c' } }).to_string(),
+        tree.to_string()
+    );
+
+    let mut tree = tree!('a');
+    tree.root_mut().reverse_children();
+    assert_eq!(tree!('a'), tree);
+}
+
+#[test]
+fn rotate_children() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd', 'e' });
+    tree.root_mut().rotate_children_left(1);
+    assert_eq!(vec!['c', 'd', 'e', 'b'], child_values(&tree));
+    tree.root_mut().rotate_children_right(2);
+    assert_eq!(vec!['e', 'b', 'c', 'd'], child_values(&tree));
+    assert_eq!(vec!['d', 'c', 'b', 'e'], child_values_rev(&tree));
+    tree.root_mut().rotate_children_left(4);
+    assert_eq!(vec!['e', 'b', 'c', 'd'], child_values(&tree));
+}
+
+#[test]
+#[should_panic]
+fn rotate_children_out_of_bounds() {
+    let mut tree = tree!('a' => { 'b' });
+    tree.root_mut().rotate_children_left(2);
+}
+
+#[test]
+fn retain_children() {
+    let mut tree = tree!('a' => { 'B' => { 'c' }, 'd', 'E', 'f' });
+    let b = tree.root().first_child().unwrap().id();
+    tree.root_mut()
+        .retain_children(|n| n.value().is_lowercase());
+    assert_eq!(vec!['d', 'f'], child_values(&tree));
+    assert_eq!(vec!['f', 'd'], child_values_rev(&tree));
+    assert_eq!(3, tree.len());
+    assert!(tree.get(b).is_none());
+}
+
+#[test]
+fn dedup_children() {
+    let mut tree = tree!('a' => { 'b', 'b' => { 'x' }, 'c', 'C', 'c', 'b' });
+    tree.root_mut()
+        .dedup_children_by_key(|n| n.value().to_ascii_lowercase());
+    assert_eq!(vec!['b', 'c', 'b'], child_values(&tree));
+    assert_eq!(4, tree.len());
+
+    let mut tree = tree!('a' => { 'b', 'c', 'd', 'f' });
+    tree.root_mut()
+        .dedup_children_by(|a, b| *a.value() as u32 == *b.value() as u32 + 1);
+    assert_eq!(vec!['b', 'd', 'f'], child_values(&tree));
+}
+
+#[test]
+fn split_children_at() {
+    let mut tree = tree!('r' => { 'a' => { 'b', 'c', 'd' }, 'e' });
+    let mut a = tree.root_mut().into_first_child().unwrap();
+    let x = a.split_children_at(1, 'x').id();
+    assert_eq!(
+        tree!('r' => { 'a' => { 'b' }, 'x' => { 'c', 'd' }, 'e' }).to_string(),
+        tree.to_string()
+    );
+    let x = tree.get(x).unwrap();
+    for child in x.children() {
+        assert_eq!(Some(x), child.parent());
+    }
+
+    let mut a = tree.root_mut().into_first_child().unwrap();
+    a.split_children_at(1, 'y');
+    a.split_children_at(0, 'z');
+    assert_eq!(
+        tree!('r' => { 'a', 'z' => { 'b' }, 'y', 'x' => { 'c', 'd' }, 'e' }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn try_split_children_at() {
+    let mut tree = tree!('r' => { 'a' => { 'b' } });
+    assert_eq!(
+        Some(Error::Root),
+        tree.root_mut().try_split_children_at(0, 'x').err()
+    );
+    assert_eq!(
+        Some(Error::Orphan),
+        tree.orphan('o').try_split_children_at(0, 'x').err()
+    );
+    let mut a = tree.root_mut().into_first_child().unwrap();
+    assert_eq!(
+        Some(Error::IndexOutOfBounds { index: 2, len: 1 }),
+        a.try_split_children_at(2, 'x').err()
+    );
+    assert_eq!(4, tree.len());
+}
+
+#[test]
+fn sort_by_cached_key() {
+    let mut tree = tree!('a' => { 'd', 'B', 'c', 'b' });
+    let mut calls = 0;
+    tree.root_mut().sort_by_cached_key(|n| {
+        calls += 1;
+        n.value().to_ascii_lowercase()
+    });
+    assert_eq!(4, calls);
+    assert_eq!(vec!['B', 'b', 'c', 'd'], child_values(&tree));
+    assert_eq!(vec!['d', 'c', 'b', 'B'], child_values_rev(&tree));
+}
+
+#[test]
+fn sort_recursive() {
+    let mut tree = tree!('a' => { 'd' => { 'g', 'e' => { 'z', 'y' } }, 'c', 'b' => { 'f' } });
+    tree.root_mut().sort_recursive();
+    assert_eq!(
+        tree!('a' => { 'b' => { 'f' }, 'c', 'd' => { 'e' => { 'y', 'z' }, 'g' } }).to_string(),
+        tree.to_string()
+    );
+
+    // Only the subtree of the node is sorted.
+    let mut tree = tree!('a' => { 'c' => { 'e', 'd' }, 'b' => { 'g', 'f' } });
+    tree.root_mut().first_child().unwrap().sort_recursive();
+    assert_eq!(
+        tree!('a' => { 'c' => { 'd', 'e' }, 'b' => { 'g', 'f' } }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn sort_recursive_by_key_stable() {
+    let mut tree = tree!("r" => { "bb" => { "x", "yy", "z" }, "a", "cc", "d" });
+    tree.root_mut().sort_recursive_by_key(|n| n.value().len());
+    assert_eq!(
+        tree!("r" => { "a", "d", "bb" => { "x", "z", "yy" }, "cc" }).to_string(),
+        tree.to_string()
+    );
+
+    let mut tree = tree!("r" => { "bb" => { "x", "yy", "z" }, "a", "cc", "d" });
+    tree.root_mut()
+        .sort_recursive_by_cached_key(|n| n.value().len());
+    assert_eq!(
+        tree!("r" => { "a", "d", "bb" => { "x", "z", "yy" }, "cc" }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn sort_recursive_deep() {
+    let mut tree = tree!(0);
+    let mut id = tree.root().id();
+    for i in 0..100_000 {
+        let mut node = tree.get_mut(id).unwrap();
+        node.append(i + 2);
+        id = node.append(i + 1).id();
+    }
+    tree.root_mut()
+        .sort_recursive_by(|a, b| b.value().cmp(a.value()));
+    let mut node = tree.root();
+    while let Some(child) = node.first_child() {
+        assert!(child.next_sibling().unwrap().value() < child.value());
+        node = child.next_sibling().unwrap();
+    }
+}
+
+#[test]
+fn insert_sorted_by_key() {
+    let mut tree = tree!('a');
+    for value in ['d', 'b', 'e', 'a', 'c', 'b'] {
+        tree.root_mut().insert_sorted_by_key(value, |&c| c);
+    }
+    assert_eq!(vec!['a', 'b', 'b', 'c', 'd', 'e'], child_values(&tree));
+    assert_eq!(vec!['e', 'd', 'c', 'b', 'b', 'a'], child_values_rev(&tree));
+    for child in tree.root().children() {
+        assert_eq!(Some(tree.root()), child.parent());
+    }
+}
+
+#[test]
+fn insert_sorted_by_key_stable() {
+    let mut tree = tree!("r" => { "a", "c", "bb" });
+    let x = tree.root_mut().insert_sorted_by_key("x", |s| s.len()).id();
+    assert_eq!(
+        vec![&"a", &"c", &"x", &"bb"],
+        tree.root()
+            .children()
+            .map(|n| n.value())
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(Some(tree.root()), tree.get(x).unwrap().parent());
+}
+
+#[test]
+fn binary_search_children_by_key() {
+    let tree = tree!('a' => { 'b', 'd', 'f' });
+    let root = tree.root();
+    assert_eq!(Ok(0), root.binary_search_children_by_key(&'b', |&c| c));
+    assert_eq!(Ok(2), root.binary_search_children_by_key(&'f', |&c| c));
+    assert_eq!(Err(0), root.binary_search_children_by_key(&'a', |&c| c));
+    assert_eq!(Err(2), root.binary_search_children_by_key(&'e', |&c| c));
+    assert_eq!(Err(3), root.binary_search_children_by_key(&'g', |&c| c));
+
+    let tree = tree!('a');
+    assert_eq!(
+        Err(0),
+        tree.root().binary_search_children_by_key(&'a', |&c| c)
+    );
+}
+
+#[test]
+fn retain_and_dedup_children_keep_root() {
+    let mut tree = tree!('a' => { 'b' });
+    let root_id = tree.root().id();
+    let mut orphan = tree.orphan('x');
+    orphan.append('y');
+    orphan.append('y');
+    assert!(orphan.try_append_id(root_id).is_err());
+    orphan.dedup_children_by_key(|node| *node.value());
+    assert_eq!(1, orphan.child_count());
+    orphan.retain_children(|_| false);
+    assert!(!orphan.has_children());
+    assert_eq!(tree!('a' => { 'b' }).to_string(), tree.to_string());
+}
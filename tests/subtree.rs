@@ -1,4 +1,4 @@
-use ego_tree::tree;
+use ego_tree::{tree, Error};
 
 #[test]
 fn prepend_subtree() {
@@ -33,3 +33,104 @@ fn append_subtree() {
     let new_tree = tree!('a' => { 'b', 'c', 'd' => { 'e', 'f' } });
     assert_eq!(format!("{:#?}", tree), format!("{:#?}", new_tree));
 }
+
+#[test]
+fn append_subtree_with_removed_nodes() {
+    let mut tree = tree!('a' => { 'b' });
+    let mut subtree = tree!('c' => { 'd', 'e' });
+    let d = subtree.root().first_child().unwrap().id();
+    subtree.remove(d);
+
+    tree.root_mut().append_subtree(subtree);
+    assert_eq!(4, tree.nodes().count());
+
+    tree.root_mut().append('f');
+    assert_eq!(5, tree.nodes().count());
+    assert_eq!(
+        vec![&'a', &'b', &'c', &'f', &'e'],
+        tree.values().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn split_off() {
+    let mut tree = tree!('a' => { 'b', 'c' => { 'd', 'e' => { 'f' } }, 'g' });
+    let c_id = tree
+        .root()
+        .first_child()
+        .unwrap()
+        .next_sibling()
+        .unwrap()
+        .id();
+
+    let subtree = tree.get_mut(c_id).unwrap().split_off();
+    assert_eq!(tree!('c' => { 'd', 'e' => { 'f' } }), subtree);
+    assert_eq!(
+        vec![&'c', &'d', &'e', &'f'],
+        subtree.values().collect::<Vec<_>>()
+    );
+    assert!(tree.get(c_id).is_none());
+    assert_eq!(tree!('a' => { 'b', 'g' }).to_string(), tree.to_string());
+    assert_eq!(3, tree.values().count());
+
+    let b_id = tree.root().first_child().unwrap().id();
+    let c_id = tree.root_mut().append_subtree(subtree).id();
+    tree.get_mut(b_id).unwrap().insert_id_after(c_id);
+    assert_eq!(
+        tree!('a' => { 'b', 'c' => { 'd', 'e' => { 'f' } }, 'g' }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(7, tree.values().count());
+}
+
+#[test]
+fn try_split_off_root() {
+    let mut tree = tree!('a' => { 'b' });
+    assert_eq!(Some(Error::Root), tree.root_mut().try_split_off().err());
+}
+
+#[test]
+fn split_off_orphan_keeps_root() {
+    let mut tree = tree!('a' => { 'b' });
+    let root_id = tree.root().id();
+    let mut orphan = tree.orphan('x');
+    assert!(orphan.try_append_id(root_id).is_err());
+    let split = orphan.split_off();
+    assert_eq!(tree!('x'), split);
+    assert_eq!(&'a', tree.root().value());
+    assert_eq!(tree!('a' => { 'b' }), tree);
+}
+
+#[test]
+fn append_subtree_mapped() {
+    let mut tree = tree!('a' => { 'b' });
+    let mut subtree = tree!('c' => { 'd', 'e' });
+    let ids = subtree.nodes().map(|n| n.id()).collect::<Vec<_>>();
+    let orphan_id = subtree.orphan('f').id();
+
+    let mut node = tree.root_mut();
+    let (mut root, map) = node.append_subtree_mapped(subtree);
+    assert_eq!(&'c', root.value());
+    assert_eq!(4, map.len());
+    for (id, value) in ids.into_iter().zip(['c', 'd', 'e']) {
+        assert_eq!(&value, tree.get(map.get(id).unwrap()).unwrap().value());
+    }
+    let orphan = tree.get(map.get(orphan_id).unwrap()).unwrap();
+    assert_eq!(&'f', orphan.value());
+    assert_eq!(None, orphan.parent());
+}
+
+#[test]
+fn prepend_subtree_mapped() {
+    let mut tree = tree!('a' => { 'b' });
+    let subtree = tree!('c' => { 'd' });
+    let d = subtree.root().first_child().unwrap().id();
+
+    let mut node = tree.root_mut();
+    let (root, map) = node.prepend_subtree_mapped(subtree);
+    let c = root.id();
+    let d = tree.get(map.get(d).unwrap()).unwrap();
+    assert_eq!(&'d', d.value());
+    assert_eq!(Some(c), d.parent().map(|n| n.id()));
+    assert_eq!(Some(c), tree.root().first_child().map(|n| n.id()));
+}
@@ -1,4 +1,4 @@
-use ego_tree::{tree, Tree};
+use ego_tree::{tree, Error, Tree};
 
 #[test]
 fn new() {
@@ -238,3 +238,612 @@ fn test_display() {
 
     assert_eq!(repr, expected);
 }
+
+#[test]
+fn remove() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.get(b).unwrap().first_child().unwrap().id();
+
+    assert_eq!(Some('b'), tree.remove(b));
+    assert!(tree.get(b).is_none());
+    assert!(tree.get(c).is_none());
+    assert_eq!(None, tree.remove(b));
+    assert_eq!(tree!('a' => { 'e' }).to_string(), tree.to_string());
+    assert_eq!(vec![&'a', &'e'], tree.values().collect::<Vec<_>>());
+}
+
+#[test]
+fn remove_reuses_slots() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    let b = tree.root().first_child().unwrap().id();
+    tree.remove(b);
+
+    let f = tree.root_mut().append('f').id();
+    assert_ne!(b, f);
+    assert!(tree.get(b).is_none());
+    tree.root_mut().append('g');
+    tree.root_mut().append('h');
+    tree.root_mut().append('i');
+
+    assert_eq!(6, tree.nodes().count());
+    assert_eq!(
+        vec![&'a', &'f', &'g', &'h', &'e', &'i'],
+        tree.values().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        tree!('a' => { 'e', 'f', 'g', 'h', 'i' }).to_string(),
+        tree.to_string()
+    );
+}
+
+#[test]
+fn remove_root() {
+    let mut tree = tree!('a' => { 'b' });
+    let root = tree.root().id();
+    assert_eq!(None, tree.remove(root));
+    assert_eq!(Err(Error::Root), tree.try_remove(root));
+    assert_eq!(tree!('a' => { 'b' }), tree);
+}
+
+#[test]
+fn try_remove() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let b = tree.root().first_child().unwrap().id();
+    assert_eq!(Ok('b'), tree.try_remove(b));
+    assert_eq!(Err(Error::InvalidId(b)), tree.try_remove(b));
+    assert_eq!(1, tree.len());
+}
+
+#[test]
+fn stale_id() {
+    let mut tree = tree!('a' => { 'b' });
+    let b = tree.root().first_child().unwrap().id();
+    tree.remove(b);
+    let c = tree.root_mut().append('c').id();
+
+    assert!(tree.get(b).is_none());
+    assert!(tree.get_mut(b).is_none());
+    assert_eq!(None, tree.remove(b));
+    assert_eq!(&'c', tree.get(c).unwrap().value());
+}
+
+#[test]
+fn node_id_size() {
+    use ego_tree::NodeId;
+    use std::mem::size_of;
+
+    assert_eq!(size_of::<NodeId>(), size_of::<Option<NodeId>>());
+}
+
+#[test]
+fn compact() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' => { 'e', 'f' } });
+    let b = tree.root().first_child().unwrap().id();
+    let d = tree.root().last_child().unwrap().id();
+    let f = tree.get(d).unwrap().last_child().unwrap().id();
+    tree.orphan('x').append('y');
+    tree.remove(b);
+    let g = tree.root_mut().prepend('g').id();
+
+    let map = tree.compact();
+
+    assert_eq!(5, map.len());
+    assert_eq!(None, map.get(b));
+    assert_eq!(Some(tree.root().id()), map.get(tree.root().id()));
+    assert_eq!(
+        vec![&'a', &'g', &'d', &'e', &'f'],
+        tree.values().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        tree!('a' => { 'g', 'd' => { 'e', 'f' } }).to_string(),
+        tree.to_string()
+    );
+
+    let new_f = tree.get(map.get(f).unwrap()).unwrap();
+    assert_eq!(&'f', new_f.value());
+    assert_eq!(Some(map.get(d).unwrap()), new_f.parent().map(|n| n.id()));
+    assert_eq!(&'g', tree.get(map.get(g).unwrap()).unwrap().value());
+    assert!(tree.get(f).is_none());
+    for (old, new) in map.iter() {
+        assert_eq!(Some(new), map.get(old));
+    }
+}
+
+#[test]
+fn transplant() {
+    let mut src = tree!('a' => { 'b', 'c' => { 'd', 'e' }, 'f' });
+    let mut dest = tree!('x' => { 'y', 'z' });
+    let c = src.root().first_child().unwrap().next_sibling().unwrap();
+    let c_id = c.id();
+    let ids = c.descendants().map(|n| n.id()).collect::<Vec<_>>();
+    let z = dest.root().last_child().unwrap().id();
+
+    let (new_c, map) = dest.transplant(&mut src, c_id, z);
+
+    assert_eq!(tree!('x' => { 'y', 'z' => { 'c' => { 'd', 'e' } } }), dest);
+    assert_eq!(Some(new_c), map.get(c_id));
+    assert_eq!(3, map.len());
+    for id in ids {
+        assert!(src.get(id).is_none());
+        let old_value = map.get(id).map(|new| *dest.get(new).unwrap().value());
+        assert!(old_value.is_some());
+    }
+
+    assert_eq!(tree!('a' => { 'b', 'f' }).to_string(), src.to_string());
+    assert_eq!(3, src.values().count());
+}
+
+#[test]
+fn try_transplant() {
+    let mut src = tree!('a' => { 'b' });
+    let mut dest = tree!('x');
+    let src_root = src.root().id();
+    let b = src.root().first_child().unwrap().id();
+    let dest_root = dest.root().id();
+
+    assert_eq!(
+        Some(Error::Root),
+        dest.try_transplant(&mut src, src_root, dest_root).err()
+    );
+    let b_in_dest = dest.transplant(&mut src, b, dest_root).0;
+    assert_eq!(
+        Some(Error::InvalidId(b)),
+        dest.try_transplant(&mut src, b, dest_root).err()
+    );
+    assert_eq!(
+        Some(Error::InvalidId(b_in_dest)),
+        src.try_transplant(&mut dest, b_in_dest, b_in_dest).err()
+    );
+    assert_eq!(tree!('a'), src);
+}
+
+#[test]
+fn len() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    assert_eq!(4, tree.len());
+    assert_eq!(4, tree.reachable_len());
+    assert_eq!(0, tree.orphaned_len());
+
+    tree.orphan('e').append('f');
+    let b = tree.root().first_child().unwrap().id();
+    tree.get_mut(b).unwrap().detach();
+    assert_eq!(6, tree.len());
+    assert_eq!(2, tree.reachable_len());
+    assert_eq!(4, tree.orphaned_len());
+
+    tree.remove(b);
+    assert_eq!(4, tree.len());
+    assert_eq!(tree.len(), tree.nodes().len());
+}
+
+#[test]
+fn capacity() {
+    let mut tree = Tree::with_capacity('a', 10);
+    assert!(tree.capacity() >= 10);
+
+    tree.reserve(100);
+    assert!(tree.capacity() >= 101);
+    tree.reserve_exact(200);
+    assert!(tree.capacity() >= 201);
+
+    let b = tree.root_mut().append('b').id();
+    tree.root_mut().append('c');
+    tree.remove(b);
+    tree.shrink_to_fit();
+    assert!(tree.capacity() >= 3);
+    assert!(tree.capacity() < 201);
+}
+
+#[test]
+fn shrink_to_fit() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    let ids = tree.root().children().map(|n| n.id()).collect::<Vec<_>>();
+    tree.remove(ids[0]);
+    tree.remove(ids[2]);
+    tree.shrink_to_fit();
+    assert_eq!(2, tree.len());
+
+    let e = tree.root_mut().append('e').id();
+    let f = tree.root_mut().append('f').id();
+    assert_eq!(
+        tree!('a' => { 'c', 'e', 'f' }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(
+        vec![&'a', &'e', &'c', &'f'],
+        tree.values().collect::<Vec<_>>()
+    );
+    for id in ids.iter().filter(|&&id| id != ids[1]) {
+        assert!(tree.get(*id).is_none());
+    }
+    assert_ne!(ids[2], f);
+    assert_ne!(ids[0], e);
+}
+
+#[test]
+fn clear() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let root = tree.root().id();
+    let b = tree.root().first_child().unwrap().id();
+    tree.orphan('d');
+    let capacity = tree.capacity();
+
+    tree.clear();
+    assert_eq!(1, tree.len());
+    assert_eq!(capacity, tree.capacity());
+    assert!(!tree.root().has_children());
+    assert_eq!(Some(tree.root()), tree.get(root));
+
+    let x = tree.root_mut().append('x').id();
+    assert_ne!(b, x);
+    assert!(tree.get(b).is_none());
+}
+
+#[test]
+fn reset() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let capacity = tree.capacity();
+    tree.reset('x');
+    assert_eq!(tree!('x'), tree);
+    assert_eq!(capacity, tree.capacity());
+    tree.root_mut().append('y');
+    assert_eq!(tree!('x' => { 'y' }).to_string(), tree.to_string());
+}
+
+#[test]
+fn compact_then_grow() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    let d = tree.root().last_child().unwrap().id();
+    tree.orphan('e');
+    let c = tree
+        .root()
+        .last_child()
+        .unwrap()
+        .prev_sibling()
+        .unwrap()
+        .id();
+    tree.remove(c);
+    tree.remove(d);
+    tree.compact();
+
+    tree.root_mut().append('f');
+    tree.root_mut().append('g');
+    assert!(tree.get(c).is_none());
+    assert!(tree.get(d).is_none());
+}
+
+#[test]
+fn set_root_orphan() {
+    let mut tree = tree!('a' => { 'b' });
+    let x = tree.orphan('x').id();
+    let a = tree.set_root(x);
+    assert_eq!(x, tree.root().id());
+    assert_eq!(tree!('x').to_string(), tree.to_string());
+    assert_eq!(None, tree.get(a).unwrap().parent());
+}
+
+#[test]
+fn set_root_descendant() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' });
+    let b = tree.root().first_child().unwrap().id();
+    let a = tree.set_root(b);
+    assert_eq!(None, tree.root().parent());
+    assert_eq!(tree!('b' => { 'c', 'd' }).to_string(), tree.to_string());
+    assert_eq!(tree!('a' => { 'e' }), tree.get(a).unwrap().to_tree());
+    assert_eq!(2, tree.orphaned_len());
+
+    let map = tree.compact();
+    assert_eq!(3, tree.len());
+    assert_eq!(map.get(b), Some(tree.root().id()));
+    assert_eq!(tree!('b' => { 'c', 'd' }).to_string(), tree.to_string());
+}
+
+#[test]
+fn try_set_root_invalid() {
+    let mut tree = tree!('a' => { 'b' });
+    let b = tree.root().first_child().unwrap().id();
+    tree.remove(b);
+    assert_eq!(Err(Error::InvalidId(b)), tree.try_set_root(b));
+}
+
+#[test]
+fn wrap_root() {
+    let mut tree = tree!('b' => { 'c' });
+    let b = tree.root().id();
+    let a = tree.wrap_root('a').id();
+    assert_eq!(a, tree.root().id());
+    assert_eq!(Some(a), tree.get(b).unwrap().parent().map(|n| n.id()));
+    assert_eq!(
+        tree!('a' => { 'b' => { 'c' } }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(
+        format!("{:#?}", tree!('a' => { 'b' => { 'c' } })),
+        format!("{tree:#?}")
+    );
+}
+
+#[test]
+fn debug_follows_root() {
+    let mut tree = tree!('a' => { 'b' });
+    let a = tree.root().id();
+    let b = tree.root().first_child().unwrap().id();
+    assert!(format!("{tree:?}").ends_with(&format!("root: {a:?} }}")));
+    tree.set_root(b);
+    assert!(format!("{tree:?}").ends_with(&format!("root: {b:?} }}")));
+    assert_eq!("Tree { 'b' }", format!("{tree:#?}"));
+}
+
+#[test]
+fn clear_after_set_root() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let b = tree.root().first_child().unwrap().id();
+    let a = tree.set_root(b);
+    tree.clear();
+    assert_eq!(1, tree.len());
+    assert_eq!(b, tree.root().id());
+    assert!(tree.get(a).is_none());
+    assert_eq!(None, tree.root().parent());
+    assert_eq!(None, tree.root().prev_sibling());
+    assert_eq!(None, tree.root().next_sibling());
+    tree.root_mut().append('x');
+    assert_eq!(tree!('b' => { 'x' }).to_string(), tree.to_string());
+}
+
+#[test]
+fn swap_nodes_adjacent() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd' });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree
+        .root()
+        .first_child()
+        .unwrap()
+        .next_sibling()
+        .unwrap()
+        .id();
+    let d = tree.root().last_child().unwrap().id();
+
+    tree.swap_nodes(b, c);
+    assert_eq!(
+        tree!('a' => { 'c', 'b', 'd' }).to_string(),
+        tree.to_string()
+    );
+    tree.swap_nodes(d, b);
+    assert_eq!(
+        tree!('a' => { 'c', 'd', 'b' }).to_string(),
+        tree.to_string()
+    );
+    tree.swap_nodes(c, b);
+    assert_eq!(
+        tree!('a' => { 'b', 'd', 'c' }).to_string(),
+        tree.to_string()
+    );
+
+    let root = tree.root();
+    assert_eq!(Some(b), root.first_child().map(|n| n.id()));
+    assert_eq!(Some(c), root.last_child().map(|n| n.id()));
+    let ids = root.children().rev().map(|n| n.id()).collect::<Vec<_>>();
+    assert_eq!(vec![c, d, b], ids);
+}
+
+#[test]
+fn swap_nodes_distant() {
+    let mut tree = tree!('a' => { 'b' => { 'c', 'd' }, 'e' => { 'f' } });
+    let b = tree.root().first_child().unwrap().id();
+    let d = tree.get(b).unwrap().last_child().unwrap().id();
+    let e = tree.root().last_child().unwrap().id();
+
+    tree.swap_nodes(d, e);
+    assert_eq!(
+        tree!('a' => { 'b' => { 'c', 'e' => { 'f' } }, 'd' }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(Some(b), tree.get(e).unwrap().parent().map(|n| n.id()));
+    assert_eq!(Some(tree.root()), tree.get(d).unwrap().parent());
+}
+
+#[test]
+fn swap_nodes_root() {
+    let mut tree = tree!('a' => { 'b' });
+    let a = tree.root().id();
+    let x = tree.orphan('x').id();
+    tree.swap_nodes(a, x);
+    assert_eq!(x, tree.root().id());
+    assert_eq!(tree!('x').to_string(), tree.to_string());
+}
+
+#[test]
+fn try_swap_nodes() {
+    let mut tree = tree!('a' => { 'b' => { 'c' } });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.get(b).unwrap().first_child().unwrap().id();
+    assert_eq!(Err(Error::WouldCycle), tree.try_swap_nodes(b, c));
+    assert_eq!(
+        Err(Error::WouldCycle),
+        tree.try_swap_nodes(c, tree.root().id())
+    );
+    assert_eq!(Ok(()), tree.try_swap_nodes(c, c));
+    tree.remove(c);
+    assert_eq!(Err(Error::InvalidId(c)), tree.try_swap_nodes(b, c));
+    assert_eq!(tree!('a' => { 'b' }), tree);
+}
+
+#[test]
+#[should_panic(expected = "Cannot swap node with its own descendant")]
+fn swap_nodes_ancestor() {
+    let mut tree = tree!('a' => { 'b' });
+    let a = tree.root().id();
+    let b = tree.root().first_child().unwrap().id();
+    tree.swap_nodes(a, b);
+}
+
+#[test]
+fn swap_values() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    let a = tree.root().id();
+    let c = tree
+        .root()
+        .first_child()
+        .unwrap()
+        .first_child()
+        .unwrap()
+        .id();
+    let d = tree.root().last_child().unwrap().id();
+    tree.swap_values(c, d);
+    tree.swap_values(d, d);
+    assert_eq!(tree!('a' => { 'b' => { 'd' }, 'c' }), tree);
+
+    assert_eq!(Err(Error::WouldCycle), tree.try_swap_values(c, a));
+    assert_eq!(Err(Error::WouldCycle), tree.try_swap_values(a, c));
+    tree.remove(d);
+    assert_eq!(Err(Error::InvalidId(d)), tree.try_swap_values(a, d));
+}
+
+#[test]
+#[should_panic(expected = "Cannot swap value with its own descendant")]
+fn swap_values_ancestor() {
+    let mut tree = tree!('a' => { 'b' });
+    let a = tree.root().id();
+    let b = tree.root().first_child().unwrap().id();
+    tree.swap_values(b, a);
+}
+
+#[test]
+fn move_sibling_range() {
+    let mut tree = tree!('a' => { 'b', 'c', 'd', 'e' => { 'f' } });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.get(b).unwrap().next_sibling().unwrap().id();
+    let d = tree.get(c).unwrap().next_sibling().unwrap().id();
+    let e = tree.root().last_child().unwrap().id();
+
+    tree.move_sibling_range(c, d, e, 1);
+    assert_eq!(
+        tree!('a' => { 'b', 'e' => { 'f', 'c', 'd' } }).to_string(),
+        tree.to_string()
+    );
+    assert_eq!(Some(e), tree.get(d).unwrap().parent().map(|n| n.id()));
+    assert_eq!(Some(d), tree.get(e).unwrap().last_child().map(|n| n.id()));
+
+    tree.move_sibling_range(c, d, e, 0);
+    assert_eq!(
+        tree!('a' => { 'b', 'e' => { 'c', 'd', 'f' } }).to_string(),
+        tree.to_string()
+    );
+
+    let root = tree.root().id();
+    tree.move_sibling_range(c, d, root, 0);
+    assert_eq!(
+        tree!('a' => { 'c', 'd', 'b', 'e' => { 'f' } }).to_string(),
+        tree.to_string()
+    );
+
+    tree.move_sibling_range(c, b, root, 1);
+    assert_eq!(
+        tree!('a' => { 'e' => { 'f' }, 'c', 'd', 'b' }).to_string(),
+        tree.to_string()
+    );
+    let values = tree
+        .root()
+        .children()
+        .rev()
+        .map(|n| *n.value())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!['b', 'd', 'c', 'e'], values);
+}
+
+#[test]
+fn try_move_sibling_range() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd', 'e' });
+    let b = tree.root().first_child().unwrap().id();
+    let c = tree.get(b).unwrap().first_child().unwrap().id();
+    let d = tree.get(b).unwrap().next_sibling().unwrap().id();
+    let e = tree.root().last_child().unwrap().id();
+    let root = tree.root().id();
+
+    assert_eq!(
+        Err(Error::Orphan),
+        tree.try_move_sibling_range(root, root, c, 0)
+    );
+    assert_eq!(
+        Err(Error::InvalidRange),
+        tree.try_move_sibling_range(d, b, c, 0)
+    );
+    assert_eq!(
+        Err(Error::InvalidRange),
+        tree.try_move_sibling_range(b, c, e, 0)
+    );
+    assert_eq!(
+        Err(Error::SelfReference),
+        tree.try_move_sibling_range(b, d, d, 0)
+    );
+    assert_eq!(
+        Err(Error::WouldCycle),
+        tree.try_move_sibling_range(b, d, c, 0)
+    );
+    assert_eq!(
+        Err(Error::IndexOutOfBounds { index: 2, len: 1 }),
+        tree.try_move_sibling_range(b, d, root, 2)
+    );
+    assert_eq!(
+        Err(Error::IndexOutOfBounds { index: 3, len: 2 }),
+        tree.try_move_sibling_range(d, d, root, 3)
+    );
+    assert_eq!(
+        Err(Error::IndexOutOfBounds { index: 1, len: 0 }),
+        tree.try_move_sibling_range(c, c, e, 1)
+    );
+    assert_eq!(tree!('a' => { 'b' => { 'c' }, 'd', 'e' }), tree);
+}
+
+#[test]
+fn sort_in_document_order() {
+    let mut tree = tree!('a' => { 'b' => { 'c' }, 'd' });
+    let x = tree.orphan('x').id();
+    tree.get_mut(x).unwrap().append('y');
+    tree.root_mut().prepend('z');
+    let b = tree.root().nth_child(1).unwrap().id();
+    tree.get_mut(b).unwrap().prepend('w');
+
+    let mut ids = tree.nodes().map(|node| node.id()).collect::<Vec<_>>();
+    ids.reverse();
+    tree.sort_in_document_order(&mut ids);
+    let values = ids.iter().map(|&id| *tree.get(id).unwrap().value());
+    assert_eq!("azbwcdxy", values.collect::<String>());
+
+    let mut ids = vec![x, b, x, tree.root().id()];
+    tree.sort_in_document_order(&mut ids);
+    assert_eq!(vec![tree.root().id(), b, x, x], ids);
+}
+
+#[test]
+#[should_panic]
+fn sort_in_document_order_invalid() {
+    let mut tree = tree!('a' => { 'b' });
+    let b = tree.root().first_child().unwrap().id();
+    tree.remove(b);
+    tree.sort_in_document_order(&mut [b]);
+}
+
+#[test]
+fn eq_ignores_vacant_slots() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(tree: &Tree<char>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        tree.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let mut tree = tree!('a' => { 'b' });
+    let c = tree.root_mut().append('c').id();
+    tree.remove(c);
+    assert_eq!(tree!('a' => { 'b' }), tree);
+    assert_eq!(hash(&tree!('a' => { 'b' })), hash(&tree));
+
+    // The new node reuses the slot with a new generation, so its ID differs.
+    tree.root_mut().append('c');
+    assert_ne!(tree!('a' => { 'b', 'c' }), tree);
+    assert_eq!(tree!('a' => { 'b', 'c' }).to_string(), tree.to_string());
+}